@@ -25,6 +25,51 @@ resolve_core_pid() {
     echo "$pid"
 }
 
+# --- cgroup Resolution ---
+# The core confines itself under a delegated cgroup (see cartesian-core.service).
+# Its worker threads live in threaded sub-groups; walk up to the domain group
+# that holds the whole process. Prints nothing on cgroup v1 or the root group.
+resolve_core_cgroup() {
+    local rel dir
+    rel=$(sed -n 's/^0:://p' "/proc/$1/cgroup")
+    [[ -z "$rel" || "$rel" == "/" ]] && return
+    dir="/sys/fs/cgroup$rel"
+    while [[ "$(cat "$dir/cgroup.type" 2>/dev/null)" == "threaded" ]]; do
+        dir=$(dirname "$dir")
+    done
+    [[ -f "$dir/cgroup.freeze" ]] && echo "$dir"
+}
+
+# --- Control Socket ---
+# The core answers cooperative pause/resume requests on a socket in its
+# owner's runtime dir; pausing work there leaves the UI responsive.
+# One line out, one line back: socat is all root needs to run for that.
+# Returns non-zero if the core can't be reached that way or refuses.
+core_control() {
+    local pid uid sock reply
+    pid=$(resolve_core_pid) || return 1
+    uid=$(stat -c %u "/proc/$pid")
+    sock="/run/user/$uid/cartesian-core.sock"
+    # Only a socket the core's owner created
+    if [[ ! -S "$sock" || "$(stat -c %u "$sock")" != "$uid" ]]; then
+        echo "Error: No control socket at $sock" >&2
+        return 1
+    fi
+    reply=$(printf '%s\n' "$*" | socat -t 2 -T 2 - "UNIX-CONNECT:$sock") || return 1
+    case "$reply" in
+        "OK "*) echo "${reply#OK }" ;;
+        "ERR "*) echo "Error: ${reply#ERR }" >&2; return 1 ;;
+        *) echo "Error: No reply from cartesian-core" >&2; return 1 ;;
+    esac
+}
+
+resolve_work() {
+    case "$1" in
+        capture|indexing|consolidation|generation|all) echo "$1" ;;
+        *) echo "Error: Unknown work '$1' (capture, indexing, consolidation, generation, all)" >&2; exit 1 ;;
+    esac
+}
+
 case "$COMMAND" in
     telemetry)
         # Safe Tier: Read-only system state
@@ -34,25 +79,64 @@ case "$COMMAND" in
         free -h
         ;;
 
+    pause|resume)
+        # Admin Tier: Hold or release one kind of work inside the core
+        WORK=$(resolve_work "$2") || exit 1
+        echo "LOG: ${COMMAND^} $WORK"
+        core_control "$COMMAND" "$WORK"
+        ;;
+
     lobotomy)
         # Admin Tier: Suspend AI
         TARGET_PID=$(resolve_core_pid)
+        # Cooperatively first: every subsystem stops, the dashboard stays alive
+        if core_control pause all; then
+            echo "LOG: Paused all Cartesian Core work (PID: $TARGET_PID)"
+            exit 0
+        fi
+        CGROUP=$(resolve_core_cgroup "$TARGET_PID")
         echo "LOG: Suspending Cartesian Core (PID: $TARGET_PID)"
-        kill -STOP "$TARGET_PID"
+        # Freezing the cgroup leaves no stopped job for the shell to reap
+        if [[ -n "$CGROUP" ]]; then
+            echo 1 > "$CGROUP/cgroup.freeze"
+        else
+            kill -STOP "$TARGET_PID"
+        fi
         ;;
 
     wakeup)
         # Admin Tier: Resume AI
         TARGET_PID=$(resolve_core_pid)
+        CGROUP=$(resolve_core_cgroup "$TARGET_PID")
         echo "LOG: Resuming Cartesian Core (PID: $TARGET_PID)"
+        if [[ -n "$CGROUP" ]]; then
+            echo 0 > "$CGROUP/cgroup.freeze"
+        fi
         kill -CONT "$TARGET_PID"
+        core_control resume all > /dev/null 2>&1 || true
         ;;
 
     leash)
         # Admin Tier: Throttle AI (Gaming Mode)
         TARGET_PID=$(resolve_core_pid)
+        # Worker threads only, so the dashboard keeps its priority
+        if core_control leash; then
+            echo "LOG: Leashed Cartesian Core workers (PID: $TARGET_PID)"
+            exit 0
+        fi
         echo "LOG: Applying Leash (renice 19) to PID: $TARGET_PID"
         renice -n 19 -p "$TARGET_PID"
+        CGROUP=$(resolve_core_cgroup "$TARGET_PID")
+        if [[ -n "$CGROUP" && -f "$CGROUP/cpu.weight" ]]; then
+            echo "LOG: Setting cpu.weight 1 on $CGROUP"
+            echo 1 > "$CGROUP/cpu.weight"
+        fi
+        ;;
+
+    unleash)
+        # Admin Tier: Lift a cooperative Leash
+        echo "LOG: Releasing Leash"
+        core_control unleash
         ;;
 
     *)
@@ -0,0 +1,246 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+
+/// The parts of the AI stack whose cost we report separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Inference,
+    Embedding,
+    Vision,
+    Indexing,
+}
+
+pub const SUBSYSTEMS: [Subsystem; 4] = [Subsystem::Inference, Subsystem::Embedding, Subsystem::Vision, Subsystem::Indexing];
+
+impl Subsystem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Subsystem::Inference => "INFERENCE",
+            Subsystem::Embedding => "EMBEDDING",
+            Subsystem::Vision => "VISION",
+            Subsystem::Indexing => "INDEXING",
+        }
+    }
+}
+
+// --- THE LEDGER ---
+// CPU time charged to each subsystem so far, and how many threads are inside
+// one right now. Spans nest (embedding inside indexing): only the innermost
+// span of a thread is charged.
+static CPU_NS: [AtomicU64; 4] = [const { AtomicU64::new(0) }; 4];
+static BUSY: [AtomicUsize; 4] = [const { AtomicUsize::new(0) }; 4];
+
+thread_local! {
+    static OPEN: RefCell<Vec<(Subsystem, u64)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn charge(subsystem: Subsystem, from: u64, to: u64) {
+    CPU_NS[subsystem as usize].fetch_add(to.saturating_sub(from), Ordering::Relaxed);
+}
+
+/// Charge the calling thread's CPU time to `subsystem` until the span drops.
+pub fn enter(subsystem: Subsystem) -> Span {
+    let now = thread_cpu_ns().unwrap_or(0);
+    OPEN.with_borrow_mut(|open| {
+        if let Some((outer, since)) = open.last() {
+            charge(*outer, *since, now);
+            BUSY[*outer as usize].fetch_sub(1, Ordering::Relaxed);
+        }
+        open.push((subsystem, now));
+    });
+    BUSY[subsystem as usize].fetch_add(1, Ordering::Relaxed);
+    Span { _not_send: std::marker::PhantomData }
+}
+
+/// Open span on this thread. Not Send: it has to close where it opened.
+pub struct Span {
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let now = thread_cpu_ns().unwrap_or(0);
+        OPEN.with_borrow_mut(|open| {
+            let Some((subsystem, since)) = open.pop() else { return; };
+            charge(subsystem, since, now);
+            BUSY[subsystem as usize].fetch_sub(1, Ordering::Relaxed);
+            // The outer span resumes from here
+            if let Some((outer, since)) = open.last_mut() {
+                *since = now;
+                BUSY[*outer as usize].fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+fn clock_ns(clock: libc::clockid_t) -> Option<u64> {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    if unsafe { libc::clock_gettime(clock, &mut ts) } != 0 {
+        return None;
+    }
+    Some(ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+}
+
+#[cfg(unix)]
+fn thread_cpu_ns() -> Option<u64> { clock_ns(libc::CLOCK_THREAD_CPUTIME_ID) }
+#[cfg(unix)]
+fn process_cpu_ns() -> Option<u64> { clock_ns(libc::CLOCK_PROCESS_CPUTIME_ID) }
+#[cfg(not(unix))]
+fn thread_cpu_ns() -> Option<u64> { None }
+#[cfg(not(unix))]
+fn process_cpu_ns() -> Option<u64> { None }
+
+/// Memory a subsystem is known to hold: loaded weights and their caches.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Resident {
+    pub ram_gb: f32,
+    pub vram_gb: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SubsystemUsage {
+    pub cpu_percent: f32, // Per-core percent, like top
+    pub busy_threads: usize,
+    pub resident: Resident,
+}
+
+/// What cartesian-core itself is using. CPU is split by subsystem; memory is
+/// measured for the whole process, and what the models hold is attributed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelfUsage {
+    pub cpu_percent: f32,
+    pub rss_bytes: Option<u64>,
+    pub gpu_bytes: Option<u64>,
+    pub threads: Option<usize>,
+    pub subsystems: BTreeMap<Subsystem, SubsystemUsage>,
+}
+
+impl SelfUsage {
+    pub fn rss_gb(&self) -> Option<f32> {
+        self.rss_bytes.map(|b| b as f32 / 1_073_741_824.0)
+    }
+
+    pub fn gpu_gb(&self) -> Option<f32> {
+        self.gpu_bytes.map(|b| b as f32 / 1_073_741_824.0)
+    }
+
+    /// Charge memory held by `subsystem` (the process totals stay measured).
+    pub fn attribute(&mut self, subsystem: Subsystem, resident: Resident) {
+        self.subsystems.entry(subsystem).or_default().resident = resident;
+    }
+
+    pub fn resident(&self, subsystem: Subsystem) -> Resident {
+        self.subsystems.get(&subsystem).map(|usage| usage.resident).unwrap_or_default()
+    }
+}
+
+/// VmRSS (bytes) and Threads from a /proc/<pid>/status.
+pub fn parse_status(status: &str) -> (Option<u64>, Option<usize>) {
+    let field = |name: &str| status.lines()
+        .find_map(|line| line.strip_prefix(name))
+        .and_then(|rest| rest.split_whitespace().next()?.parse::<u64>().ok());
+    (field("VmRSS:").map(|kb| kb * 1024), field("Threads:").map(|n| n as usize))
+}
+
+/// THE MIRROR
+/// Turns the ledger's running totals into rates between two samples.
+pub struct SelfAccountant {
+    last: Option<(Instant, u64, [u64; 4])>,
+}
+
+impl SelfAccountant {
+    pub fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// `gpu_bytes` comes from the VRAM probe's per-process usage, if any.
+    pub fn sample(&mut self, gpu_bytes: Option<u64>) -> SelfUsage {
+        let now = Instant::now();
+        let process_ns = process_cpu_ns().unwrap_or(0);
+        let charged: [u64; 4] = std::array::from_fn(|i| CPU_NS[i].load(Ordering::Relaxed));
+        let (rss_bytes, threads) = std::fs::read_to_string("/proc/self/status")
+            .map(|status| parse_status(&status))
+            .unwrap_or_default();
+
+        let mut usage = SelfUsage { rss_bytes, gpu_bytes, threads, ..Default::default() };
+        if let Some((at, last_process_ns, last_charged)) = self.last {
+            let wall_ns = (now - at).as_nanos().max(1) as f32;
+            let percent = |ns: u64| ns as f32 / wall_ns * 100.0;
+            usage.cpu_percent = percent(process_ns.saturating_sub(last_process_ns));
+            for subsystem in SUBSYSTEMS {
+                let i = subsystem as usize;
+                usage.subsystems.insert(subsystem, SubsystemUsage {
+                    cpu_percent: percent(charged[i].saturating_sub(last_charged[i])),
+                    busy_threads: BUSY[i].load(Ordering::Relaxed),
+                    ..Default::default()
+                });
+            }
+        }
+        self.last = Some((now, process_ns, charged));
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_fields_parse() {
+        let status = "Name:\tcartesian-core\nVmPeak:\t 9000 kB\nVmRSS:\t  524288 kB\nThreads:\t17\n";
+        assert_eq!(parse_status(status), (Some(536_870_912), Some(17)));
+        assert_eq!(parse_status("Name:\tx\n"), (None, None));
+    }
+
+    #[test]
+    fn resident_memory_is_attributed_per_subsystem() {
+        let mut usage = SelfUsage { rss_bytes: Some(4 << 30), ..Default::default() };
+        assert_eq!(usage.resident(Subsystem::Inference), Resident::default());
+
+        usage.attribute(Subsystem::Inference, Resident { ram_gb: 0.0, vram_gb: 3.5 });
+        usage.attribute(Subsystem::Embedding, Resident { ram_gb: 0.1, vram_gb: 0.0 });
+        assert_eq!(usage.resident(Subsystem::Inference).vram_gb, 3.5);
+        assert_eq!(usage.resident(Subsystem::Embedding).ram_gb, 0.1);
+        // Attribution doesn't touch what was measured
+        assert_eq!(usage.rss_gb(), Some(4.0));
+        assert_eq!(usage.subsystems[&Subsystem::Inference].cpu_percent, 0.0);
+    }
+
+    #[cfg(unix)]
+    /// Burn `ms` of this thread's CPU time (not wall time: the box may be busy).
+    fn spin(ms: u64) {
+        let until = thread_cpu_ns().unwrap() + ms * 1_000_000;
+        let mut x = 0u64;
+        while thread_cpu_ns().unwrap() < until {
+            x = std::hint::black_box(x.wrapping_add(1));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn nested_spans_charge_the_innermost() {
+        // Vision is ours alone here; other tests may hold the rest
+        let charged = || CPU_NS[Subsystem::Vision as usize].load(Ordering::Relaxed);
+        let before = charged();
+        let embedding_before = CPU_NS[Subsystem::Embedding as usize].load(Ordering::Relaxed);
+        let outer = enter(Subsystem::Vision);
+        spin(30);
+        {
+            let _inner = enter(Subsystem::Embedding);
+            let paused = charged();
+            spin(30);
+            assert_eq!(charged(), paused, "vision paused while embedding");
+        }
+        drop(outer);
+
+        let vision_ms = (charged() - before) / 1_000_000;
+        let embedding_ms = (CPU_NS[Subsystem::Embedding as usize].load(Ordering::Relaxed) - embedding_before) / 1_000_000;
+        assert!((30..200).contains(&vision_ms), "vision {} ms", vision_ms);
+        assert!(embedding_ms >= 30, "embedding {} ms", embedding_ms);
+    }
+}
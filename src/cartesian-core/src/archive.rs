@@ -0,0 +1,198 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::hippocampus::Engram;
+use crate::vault::Vault;
+
+// --- ARCHIVE LAYOUT ---
+// [MAGIC 4][MANIFEST_LEN u32 LE][MANIFEST JSON][ZSTD PAYLOAD]
+const MAGIC: &[u8; 4] = b"CXAR";
+pub const FORMAT_VERSION: u32 = 1;
+
+/// THE ARCHIVE
+/// Portable export of the Hippocampus: a single versioned, checksummed file
+/// holding every engram (chunks, embeddings, LTP counters, entity maps, synapses).
+///
+/// The manifest describes the export. It is stored uncompressed so it can be
+/// inspected without unpacking the payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub created_at: u64, // Unix seconds
+    pub engram_count: usize,
+    pub chunk_count: usize,
+    pub synapse_count: usize,
+    pub payload_bytes: usize,
+    pub payload_sha256: String,
+}
+
+/// Rewrites a path prefix on import, e.g. `/home/alice` -> `/home/bob`.
+#[derive(Debug, Clone)]
+pub struct PathRemap {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl PathRemap {
+    /// Parses `FROM=TO`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (from, to) = spec.split_once('=')?;
+        Some(Self { from: PathBuf::from(from), to: PathBuf::from(to) })
+    }
+
+    pub fn apply(remaps: &[PathRemap], path: &Path) -> PathBuf {
+        for remap in remaps {
+            if let Ok(rest) = path.strip_prefix(&remap.from) {
+                return remap.to.join(rest);
+            }
+        }
+        path.to_path_buf()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    /// Keep the local library and fold the archive into it.
+    Merge,
+    /// Drop the local library and take the archive as-is.
+    Replace,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: usize,
+    pub merged: usize,
+    pub skipped_sensitive: usize,
+}
+
+/// Serialize engrams into a complete archive.
+pub fn pack(engrams: &[&Engram]) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(engrams).map_err(|e| e.to_string())?;
+    let payload = zstd::encode_all(Cursor::new(json), 19)
+        .map_err(|e| format!("Compression failed: {}", e))?;
+
+    let manifest = ArchiveManifest {
+        format_version: FORMAT_VERSION,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        engram_count: engrams.len(),
+        chunk_count: engrams.iter().map(|e| e.chunks.len()).sum(),
+        synapse_count: engrams.iter().map(|e| e.synapses.len()).sum(),
+        payload_bytes: payload.len(),
+        payload_sha256: sha256_hex(&payload),
+    };
+    let manifest_json = serde_json::to_vec(&manifest).map_err(|e| e.to_string())?;
+
+    let mut out = Vec::with_capacity(8 + manifest_json.len() + payload.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&(manifest_json.len() as u32).to_le_bytes());
+    out.extend_from_slice(&manifest_json);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+/// Read an archive file, opening it first if it is sealed.
+pub fn read(path: &Path, vault: Option<&Vault>) -> Result<Vec<u8>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("{:?}: {}", path, e))?;
+    if !Vault::is_sealed(&data) {
+        return Ok(data);
+    }
+    vault.ok_or("Archive: Archive is encrypted but no key is configured.")?.open(&data)
+}
+
+/// Read only the manifest.
+pub fn read_manifest(data: &[u8]) -> Result<(ArchiveManifest, &[u8]), String> {
+    if data.len() < 8 || &data[0..4] != MAGIC {
+        return Err("Archive: Not a Hippocampus archive.".to_string());
+    }
+    let manifest_len = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+    let manifest_end = 8 + manifest_len;
+    if data.len() < manifest_end {
+        return Err("Archive: Truncated manifest.".to_string());
+    }
+
+    let manifest: ArchiveManifest = serde_json::from_slice(&data[8..manifest_end])
+        .map_err(|e| format!("Archive: Bad manifest: {}", e))?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "Archive: Format v{} is newer than supported v{}.",
+            manifest.format_version, FORMAT_VERSION
+        ));
+    }
+    Ok((manifest, &data[manifest_end..]))
+}
+
+/// Verify the checksum and decode every engram.
+pub fn unpack(data: &[u8]) -> Result<(ArchiveManifest, Vec<Engram>), String> {
+    let (manifest, payload) = read_manifest(data)?;
+    if payload.len() != manifest.payload_bytes || sha256_hex(payload) != manifest.payload_sha256 {
+        return Err("Archive: Checksum mismatch (corrupted or truncated).".to_string());
+    }
+
+    let json = zstd::decode_all(Cursor::new(payload))
+        .map_err(|e| format!("Decompression failed: {}", e))?;
+    let engrams = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    Ok((manifest, engrams))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> Vec<u8> {
+        pack(&[]).unwrap()
+    }
+
+    /// The same archive with its manifest edited.
+    fn with_manifest(data: &[u8], edit: impl FnOnce(&mut ArchiveManifest)) -> Vec<u8> {
+        let (mut manifest, payload) = read_manifest(data).unwrap();
+        edit(&mut manifest);
+        let json = serde_json::to_vec(&manifest).unwrap();
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        out.extend_from_slice(&json);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[test]
+    fn intact_archives_unpack() {
+        let (manifest, engrams) = unpack(&archive()).unwrap();
+        assert_eq!(manifest.format_version, FORMAT_VERSION);
+        assert!(engrams.is_empty());
+    }
+
+    #[test]
+    fn foreign_and_newer_files_are_refused() {
+        let mut foreign = archive();
+        foreign[0] ^= 0xff;
+        assert_eq!(unpack(&foreign).unwrap_err(), "Archive: Not a Hippocampus archive.");
+
+        let newer = with_manifest(&archive(), |m| m.format_version = FORMAT_VERSION + 1);
+        assert_eq!(unpack(&newer).unwrap_err(), format!(
+            "Archive: Format v{} is newer than supported v{}.", FORMAT_VERSION + 1, FORMAT_VERSION
+        ));
+    }
+
+    #[test]
+    fn corrupted_payloads_fail_the_checksum() {
+        let checksum = "Archive: Checksum mismatch (corrupted or truncated).";
+        let mut flipped = archive();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert_eq!(unpack(&flipped).unwrap_err(), checksum);
+
+        let mut truncated = archive();
+        truncated.pop();
+        assert_eq!(unpack(&truncated).unwrap_err(), checksum);
+
+        let relabelled = with_manifest(&archive(), |m| m.payload_sha256 = "0".repeat(64));
+        assert_eq!(unpack(&relabelled).unwrap_err(), checksum);
+    }
+}
@@ -1,3 +1,8 @@
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use crate::categories::AudioProfile;
+use crate::config;
+
 /// THE AUDIO CONSOLE
 /// Manages Virtual Sinks (Game, Voice, Music) via PipeWire.
 
@@ -7,6 +12,18 @@ pub struct AudioState {
     pub voice_vol: f32,
     pub music_vol: f32,
     pub mic_muted: bool,
+    pub profile: AudioProfile, // From the active categories
+}
+
+impl AudioState {
+    /// What the Music sink actually plays at: the user's level, ducked or muted by the profile.
+    pub fn effective_music_vol(&self) -> f32 {
+        match self.profile {
+            AudioProfile::Normal => self.music_vol,
+            AudioProfile::DuckMusic => self.music_vol * config::AUDIO_DUCK_LEVEL,
+            AudioProfile::MuteMusic => 0.0,
+        }
+    }
 }
 
 impl Default for AudioState {
@@ -16,6 +33,7 @@ impl Default for AudioState {
             voice_vol: 1.0,
             music_vol: 0.5,
             mic_muted: false,
+            profile: AudioProfile::Normal,
         }
     }
 }
@@ -23,12 +41,14 @@ impl Default for AudioState {
 pub struct Mixer {
     state: AudioState,
     // pipewire_context: Option<Context>, // TODO: Phase 6
+    ducked: Arc<Mutex<Vec<MusicStream>>>, // Music streams we turned down, at their own volume
 }
 
 impl Mixer {
     pub fn new() -> Self {
         Self {
             state: AudioState::default(),
+            ducked: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -46,7 +66,138 @@ impl Mixer {
         // TODO: Send IPC command to PipeWire here
     }
 
+    /// Duck, mute or restore the music players' streams. `pactl` runs on a
+    /// short-lived thread; streams that start while ducked keep their level.
+    pub fn set_profile(&mut self, profile: AudioProfile) {
+        if self.state.profile != profile {
+            println!("Audio: Music {} ({:.0}%)", profile.label(), self.state.music_vol * 100.0);
+            self.state.profile = profile;
+            let ducked = self.ducked.clone();
+            std::thread::spawn(move || {
+                let mut ducked = ducked.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = apply_profile(profile, &mut ducked) {
+                    eprintln!("Audio: Music not {}: {}", profile.label().to_lowercase(), e);
+                }
+            });
+        }
+    }
+
     pub fn toggle_mic(&mut self) {
         self.state.mic_muted = !self.state.mic_muted;
     }
-}
\ No newline at end of file
+}
+
+// --- MUSIC STREAMS (pactl, on PipeWire or PulseAudio) ---
+
+#[derive(Debug, Clone, PartialEq)]
+struct MusicStream {
+    index: u32,
+    volume_percent: u32, // Before we touched it
+}
+
+fn apply_profile(profile: AudioProfile, ducked: &mut Vec<MusicStream>) -> Result<(), String> {
+    let factor = match profile {
+        AudioProfile::Normal => {
+            // Streams that ended meanwhile just fail
+            for stream in ducked.drain(..) {
+                let _ = set_volume(stream.index, stream.volume_percent);
+            }
+            return Ok(());
+        }
+        AudioProfile::DuckMusic => config::AUDIO_DUCK_LEVEL,
+        AudioProfile::MuteMusic => 0.0,
+    };
+
+    for stream in music_streams(&pactl(&["list", "sink-inputs"])?) {
+        if !ducked.iter().any(|d| d.index == stream.index) {
+            ducked.push(stream);
+        }
+    }
+    for stream in ducked.iter() {
+        set_volume(stream.index, (stream.volume_percent as f32 * factor).round() as u32)?;
+    }
+    Ok(())
+}
+
+fn set_volume(index: u32, percent: u32) -> Result<String, String> {
+    pactl(&["set-sink-input-volume", &index.to_string(), &format!("{}%", percent)])
+}
+
+fn pactl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C") // The listing is parsed
+        .output()
+        .map_err(|e| format!("pactl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("pactl {}: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Streams of known music players in `pactl list sink-inputs` output.
+fn music_streams(listing: &str) -> Vec<MusicStream> {
+    let mut streams = Vec::new();
+    let mut current: Option<(u32, u32, bool)> = None; // Index, volume, is music
+    for line in listing.lines().map(str::trim) {
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            streams.extend(current.take().filter(|s| s.2).map(|(index, volume_percent, _)| MusicStream { index, volume_percent }));
+            current = index.parse().ok().map(|index| (index, 100, false));
+            continue;
+        }
+        let Some(stream) = current.as_mut() else { continue; };
+        if let Some(volume) = line.strip_prefix("Volume:") {
+            // "front-left: 32768 /  50% / -18.06 dB, ..."
+            if let Some(percent) = volume.split_whitespace().find_map(|w| w.strip_suffix('%')?.parse().ok()) {
+                stream.1 = percent;
+            }
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            let value = value.trim_matches('"').to_lowercase();
+            if matches!(key, "application.process.binary" | "application.name") && config::MUSIC_PLAYERS.contains(&value.as_str()) {
+                stream.2 = true;
+            }
+        }
+    }
+    streams.extend(current.filter(|s| s.2).map(|(index, volume_percent, _)| MusicStream { index, volume_percent }));
+    streams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn music_streams_are_picked_out() {
+        let listing = r#"Sink Input #41
+	Driver: PipeWire
+	Volume: front-left: 42598 /  65% / -11.23 dB,   front-right: 42598 /  65% / -11.23 dB
+	Properties:
+		application.name = "Spotify"
+		application.process.binary = "spotify"
+Sink Input #57
+	Volume: front-left: 65536 / 100% / 0.00 dB
+	Properties:
+		application.name = "Firefox"
+		application.process.binary = "firefox"
+Sink Input #60
+	Volume: mono: 32768 /  50% / -18.06 dB
+	Properties:
+		application.process.binary = "mpd"
+"#;
+        assert_eq!(music_streams(listing), vec![
+            MusicStream { index: 41, volume_percent: 65 },
+            MusicStream { index: 60, volume_percent: 50 },
+        ]);
+        assert!(music_streams("").is_empty());
+    }
+
+    #[test]
+    fn profile_sets_the_effective_music_level() {
+        let mut state = AudioState { music_vol: 0.5, ..Default::default() };
+        assert_eq!(state.effective_music_vol(), 0.5);
+        state.profile = AudioProfile::DuckMusic;
+        assert_eq!(state.effective_music_vol(), 0.5 * config::AUDIO_DUCK_LEVEL);
+        state.profile = AudioProfile::MuteMusic;
+        assert_eq!(state.effective_music_vol(), 0.0);
+    }
+}
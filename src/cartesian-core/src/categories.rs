@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use serde::{Serialize, Deserialize};
+
+use crate::inference::GovernorState;
+use crate::lobotomy::{self, AppCategory};
+
+pub const CATEGORIES_VERSION: u32 = 1;
+/// Shipped as a custom category so users can retune it like their own.
+pub const VIDEO_CALL: &str = "VideoCall";
+
+// --- THE NAME TABLE ---
+// Custom categories travel as `&'static str` so AppCategory stays Copy.
+// Each name is leaked once, when it is first defined. Tests get a table per
+// thread, so what one test defines is invisible to the others.
+fn with_defined<T>(f: impl FnOnce(&mut Vec<&'static str>) -> T) -> T {
+    #[cfg(not(test))]
+    {
+        static DEFINED: LazyLock<Mutex<Vec<&'static str>>> = LazyLock::new(|| Mutex::new(vec![VIDEO_CALL]));
+        f(&mut DEFINED.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+    #[cfg(test)]
+    {
+        thread_local! {
+            static DEFINED: std::cell::RefCell<Vec<&'static str>> = std::cell::RefCell::new(vec![VIDEO_CALL]);
+        }
+        DEFINED.with_borrow_mut(f)
+    }
+}
+
+/// The custom category called `name`, if one has been defined.
+pub fn lookup(name: &str) -> Option<&'static str> {
+    with_defined(|defined| defined.iter().find(|n| **n == name).copied())
+}
+
+fn define(name: &str) -> &'static str {
+    with_defined(|defined| match defined.iter().find(|n| **n == name) {
+        Some(n) => *n,
+        None => {
+            let n: &'static str = Box::leak(name.to_string().into_boxed_str());
+            defined.push(n);
+            n
+        }
+    })
+}
+
+static WARNED: LazyLock<Mutex<BTreeSet<String>>> = LazyLock::new(|| Mutex::new(BTreeSet::new()));
+
+/// Once per name: a file uses a category that isn't defined (any more).
+pub fn warn_undefined(name: &str) {
+    let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+    if warned.insert(name.to_string()) {
+        eprintln!("Categories: '{}' is not defined, treated as Unknown", name);
+    }
+}
+
+/// How music is treated while a category is active. Stronger variants win.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioProfile {
+    #[default]
+    Normal,
+    DuckMusic,
+    MuteMusic,
+}
+
+impl AudioProfile {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AudioProfile::Normal => "NORMAL",
+            AudioProfile::DuckMusic => "DUCKED",
+            AudioProfile::MuteMusic => "MUTED",
+        }
+    }
+}
+
+/// What a category asks of the rest of the system while it is active.
+/// Unset fields leave the default (allowed, normal audio, no ceiling).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Behaviour {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_state: Option<GovernorState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioProfile>,
+    /// May the Witness capture the screen?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness: Option<bool>,
+    /// May the Hippocampus index in the background?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexing: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryFile {
+    pub version: u32,
+    #[serde(default)]
+    pub categories: BTreeMap<String, Behaviour>,
+}
+
+/// Everything the active categories ask for, combined: the strictest
+/// ceiling, the strongest audio profile, and a veto from any of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Effects {
+    pub max_state: Option<(AppCategory, GovernorState)>,
+    pub audio: AudioProfile,
+    pub witness: bool,
+    pub indexing: bool,
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self { max_state: None, audio: AudioProfile::Normal, witness: true, indexing: true }
+    }
+}
+
+/// THE CATEGORY BOOK
+/// Behaviours per category: the built-in ones, then categories.json on top
+/// (a user entry replaces the built-in behaviour for that name).
+#[derive(Debug, Clone)]
+pub struct Categories {
+    behaviours: HashMap<AppCategory, Behaviour>,
+}
+
+impl Categories {
+    pub fn builtin() -> Self {
+        let behaviours = HashMap::from([
+            // Visual engrams never compete with a game
+            (AppCategory::Game, Behaviour { witness: Some(false), ..Default::default() }),
+            // Nobody wants the call (or the screen share) on record, or music over it
+            (AppCategory::Custom(VIDEO_CALL), Behaviour {
+                audio: Some(AudioProfile::DuckMusic),
+                witness: Some(false),
+                ..Default::default()
+            }),
+        ]);
+        Self { behaviours }
+    }
+
+    /// Built-in behaviours plus `data`, defining its new category names.
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let file: CategoryFile = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        validate(&file).map_err(|errors| errors.join("; "))?;
+
+        let mut categories = Self::builtin();
+        for (name, behaviour) in file.categories {
+            let category = AppCategory::try_from(name.clone())
+                .unwrap_or_else(|_| AppCategory::Custom(define(&name)));
+            categories.behaviours.insert(category, behaviour);
+        }
+        Ok(categories)
+    }
+
+    /// The built-in behaviours if the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::builtin());
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("{:?}: {}", path, e))?;
+        Self::parse(&data).map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    /// Every category an app can be filed under: the built-in ones, then the custom ones by name.
+    pub fn all(&self) -> Vec<AppCategory> {
+        let mut custom: Vec<AppCategory> = self.behaviours.keys()
+            .filter(|c| matches!(c, AppCategory::Custom(_)))
+            .copied()
+            .collect();
+        custom.sort_by_key(|c| c.name());
+        lobotomy::CATEGORIES.into_iter().chain(custom).collect()
+    }
+
+    pub fn get(&self, category: AppCategory) -> Option<&Behaviour> {
+        self.behaviours.get(&category)
+    }
+
+    pub fn effects(&self, active: &[AppCategory]) -> Effects {
+        let mut effects = Effects::default();
+        for category in active {
+            let Some(behaviour) = self.behaviours.get(category) else { continue; };
+            if let Some(max) = behaviour.max_state {
+                if effects.max_state.is_none_or(|(_, strictest)| max.rank() < strictest.rank()) {
+                    effects.max_state = Some((*category, max));
+                }
+            }
+            effects.audio = effects.audio.max(behaviour.audio.unwrap_or_default());
+            effects.witness &= behaviour.witness.unwrap_or(true);
+            effects.indexing &= behaviour.indexing.unwrap_or(true);
+        }
+        effects
+    }
+}
+
+fn validate(file: &CategoryFile) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    if file.version != CATEGORIES_VERSION {
+        errors.push(format!("version {} is not supported (expected {})", file.version, CATEGORIES_VERSION));
+    }
+    for name in file.categories.keys() {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            errors.push(format!("category '{}': names are letters and digits only", name));
+        }
+        if name == AppCategory::Unknown.name() {
+            errors.push("category 'Unknown': only known categories have behaviour".to_string());
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_defines_categories_and_overrides_builtins() {
+        let categories = Categories::parse(r#"{
+            "version": 1,
+            "categories": {
+                "Compile": { "max_state": "SidekickMode", "indexing": false },
+                "Game": { "witness": true }
+            }
+        }"#).unwrap();
+
+        let compile = AppCategory::try_from("Compile".to_string()).unwrap();
+        assert_eq!(compile, AppCategory::Custom("Compile"));
+        assert_eq!(categories.get(compile).and_then(|b| b.max_state), Some(GovernorState::SidekickMode));
+        assert_eq!(categories.get(AppCategory::Game).and_then(|b| b.witness), Some(true));
+        // Built-ins the file doesn't mention stay
+        assert!(categories.get(AppCategory::Custom(VIDEO_CALL)).is_some());
+        assert_eq!(categories.all().last(), Some(&AppCategory::Custom(VIDEO_CALL)));
+
+        assert!(AppCategory::try_from("Streaming".to_string()).is_err());
+        let json = serde_json::to_string(&compile).unwrap();
+        assert_eq!(json, "\"Compile\"");
+        assert_eq!(serde_json::from_str::<AppCategory>(&json).unwrap(), compile);
+        // A category that was removed since doesn't break the files using it
+        assert_eq!(serde_json::from_str::<AppCategory>("\"Streaming\"").unwrap(), AppCategory::Unknown);
+    }
+
+    #[test]
+    fn definitions_stay_with_their_test() {
+        assert!(lookup("Compile").is_none(), "defined by another test");
+        let other = std::thread::spawn(|| Categories::parse(r#"{ "version": 1, "categories": { "Compile": {} } }"#).is_ok());
+        assert!(other.join().unwrap());
+        assert!(lookup("Compile").is_none());
+        assert_eq!(lookup(VIDEO_CALL), Some(VIDEO_CALL));
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        assert!(Categories::parse(r#"{ "version": 2, "categories": {} }"#).is_err());
+        assert!(Categories::parse(r#"{ "version": 1, "categories": { "Video Call": {} } }"#).is_err());
+        assert!(Categories::parse(r#"{ "version": 1, "categories": { "Unknown": {} } }"#).is_err());
+        assert!(Categories::parse(r#"{ "version": 1, "categories": { "Stream": { "audio": "loud" } } }"#).is_err());
+    }
+
+    #[test]
+    fn active_categories_combine() {
+        let categories = Categories::builtin();
+        assert_eq!(categories.effects(&[]), Effects::default());
+        assert_eq!(categories.effects(&[AppCategory::Web, AppCategory::Development]), Effects::default());
+
+        let call = categories.effects(&[AppCategory::Web, AppCategory::Custom(VIDEO_CALL)]);
+        assert_eq!(call.audio, AudioProfile::DuckMusic);
+        assert!(!call.witness);
+        assert!(call.indexing);
+
+        let mut strict = Categories::builtin();
+        strict.behaviours.insert(AppCategory::Production, Behaviour {
+            max_state: Some(GovernorState::Conscientious),
+            audio: Some(AudioProfile::MuteMusic),
+            ..Default::default()
+        });
+        strict.behaviours.insert(AppCategory::Game, Behaviour {
+            max_state: Some(GovernorState::PotatoMode),
+            indexing: Some(false),
+            ..Default::default()
+        });
+        let both = strict.effects(&[AppCategory::Production, AppCategory::Custom(VIDEO_CALL), AppCategory::Game]);
+        assert_eq!(both.max_state, Some((AppCategory::Game, GovernorState::PotatoMode)));
+        assert_eq!(both.audio, AudioProfile::MuteMusic);
+        assert!(!both.witness && !both.indexing);
+    }
+}
@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use crate::inference::{BackgroundBudget, BackgroundJob, EngineProfile};
+use crate::resources::thread_id;
+
+const CGROUP_FS: &str = "/sys/fs/cgroup";
+const CPU_PERIOD_US: u64 = 100_000;
+
+static CONFINEMENT: OnceLock<Confinement> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    Inference,
+    Indexing,
+    Vision,
+}
+
+impl Group {
+    fn dir_name(&self) -> &'static str {
+        match self {
+            Group::Inference => "inference",
+            Group::Indexing => "indexing",
+            Group::Vision => "vision",
+        }
+    }
+}
+
+/// THE LEASH (cgroup v2)
+/// Layout under the delegated cgroup we were started in:
+///
+///   <delegated>/                  +cpu +memory for children
+///   <delegated>/cartesian/        the process; memory.high
+///   <delegated>/cartesian/inference   threaded: cpu.weight, cpu.max
+///   <delegated>/cartesian/indexing    threaded
+///   <delegated>/cartesian/vision      threaded; frozen while paused
+///
+/// memory is not a threaded controller, so the memory limit covers the whole
+/// process. Indexing is never frozen: the worker may hold the Hippocampus
+/// write lock, and it already idles on its own while paused.
+pub struct Confinement {
+    root: PathBuf,
+    applied: Mutex<Option<EngineProfile>>,
+}
+
+impl Confinement {
+    /// Build the layout and move the process in. Needs a delegated cgroup,
+    /// e.g. the packaged user unit or `systemd-run --user -p Delegate=yes`.
+    pub fn setup() -> Result<Self, String> {
+        let own = own_cgroup().ok_or("Not on the cgroup v2 unified hierarchy")?;
+        if own == "/" {
+            return Err("Running in the root cgroup; start cartesian-core from a delegated unit".to_string());
+        }
+        let mut delegated = Path::new(CGROUP_FS).join(own.trim_start_matches('/'));
+        // Already inside our own layout (restart via exec)
+        while delegated.file_name().is_some_and(|n| n == "cartesian" || is_group_name(n.to_str())) {
+            delegated.pop();
+        }
+        let root = delegated.join("cartesian");
+
+        fs::create_dir_all(&root).map_err(|e| format!("{:?}: {} (is the cgroup delegated?)", root, e))?;
+        write(&root.join("cgroup.procs"), &std::process::id().to_string())?;
+        write(&delegated.join("cgroup.subtree_control"), "+cpu +memory")
+            .map_err(|e| format!("{} (Delegate= must include cpu and memory)", e))?;
+
+        for group in [Group::Inference, Group::Indexing, Group::Vision] {
+            let dir = root.join(group.dir_name());
+            fs::create_dir_all(&dir).map_err(|e| format!("{:?}: {}", dir, e))?;
+            if fs::read_to_string(dir.join("cgroup.type")).map(|t| t.trim() != "threaded").unwrap_or(true) {
+                write(&dir.join("cgroup.type"), "threaded")?;
+            }
+        }
+        write(&root.join("cgroup.subtree_control"), "+cpu")?;
+
+        Ok(Self::at(root))
+    }
+
+    pub fn at(root: PathBuf) -> Self {
+        Self { root, applied: Mutex::new(None) }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Push a state's limits into the tree. No-op if nothing changed; after
+    /// a failed write the whole profile is tried again on the next call.
+    pub fn apply(&self, profile: &EngineProfile) -> Result<(), String> {
+        let mut applied = self.applied.lock().map_err(|_| "Confinement lock poisoned")?;
+        if applied.as_ref() == Some(profile) {
+            return Ok(());
+        }
+        *applied = None;
+        self.write_profile(profile)?;
+        *applied = Some(profile.clone());
+        Ok(())
+    }
+
+    fn write_profile(&self, profile: &EngineProfile) -> Result<(), String> {
+        let inference = self.root.join(Group::Inference.dir_name());
+        write(&inference.join("cpu.weight"), &profile.cpu_weight.to_string())?;
+        write(&inference.join("cpu.max"), &cpu_max(profile.cpu_max))?;
+
+        // Background groups follow their job's budget
+        for (group, job) in [(Group::Indexing, BackgroundJob::Consolidation), (Group::Vision, BackgroundJob::Witness)] {
+            let (weight, max) = match profile.budget(job) {
+                BackgroundBudget::Full => (100, None),
+                BackgroundBudget::Throttled => (20, Some(1.0)),
+                BackgroundBudget::Paused => (1, Some(0.1)),
+            };
+            let dir = self.root.join(group.dir_name());
+            write(&dir.join("cpu.weight"), &weight.to_string())?;
+            write(&dir.join("cpu.max"), &cpu_max(max))?;
+        }
+        let frozen = profile.budget(BackgroundJob::Witness) == BackgroundBudget::Paused;
+        write(&self.root.join(Group::Vision.dir_name()).join("cgroup.freeze"), if frozen { "1" } else { "0" })?;
+
+        let memory_high = match profile.memory_high_gb {
+            Some(gb) => (((gb as f64) * 1_073_741_824.0) as u64).to_string(),
+            None => "max".to_string(),
+        };
+        write(&self.root.join("memory.high"), &memory_high)
+    }
+
+    fn move_thread(&self, dir: &Path) {
+        if let Some(tid) = thread_id() {
+            if let Err(e) = write(&dir.join("cgroup.threads"), &tid.to_string()) {
+                eprintln!("Confinement: {}", e);
+            }
+        }
+    }
+}
+
+/// Set up confinement once per process. Later calls return the first result.
+pub fn init() -> Result<&'static Confinement, String> {
+    if let Some(c) = CONFINEMENT.get() {
+        return Ok(c);
+    }
+    let confinement = Confinement::setup()?;
+    Ok(CONFINEMENT.get_or_init(|| confinement))
+}
+
+pub fn get() -> Option<&'static Confinement> {
+    CONFINEMENT.get()
+}
+
+/// Move the calling thread into `group` for good (dedicated threads).
+pub fn join(group: Group) {
+    if let Some(c) = get() {
+        c.move_thread(&c.root.join(group.dir_name()));
+    }
+}
+
+/// Move the calling thread into `group` until the guard drops. For pooled
+/// threads (tokio's blocking pool) that must not stay confined afterwards.
+pub fn enter(group: Group) -> ThreadGuard {
+    join(group);
+    ThreadGuard
+}
+
+pub struct ThreadGuard;
+
+impl Drop for ThreadGuard {
+    fn drop(&mut self) {
+        if let Some(c) = get() {
+            c.move_thread(&c.root);
+        }
+    }
+}
+
+fn cpu_max(cpus: Option<f32>) -> String {
+    match cpus {
+        Some(cpus) => format!("{} {}", ((cpus as f64) * CPU_PERIOD_US as f64).max(1000.0) as u64, CPU_PERIOD_US),
+        None => format!("max {}", CPU_PERIOD_US),
+    }
+}
+
+fn is_group_name(name: Option<&str>) -> bool {
+    matches!(name, Some("inference" | "indexing" | "vision"))
+}
+
+fn own_cgroup() -> Option<String> {
+    fs::read_to_string("/proc/self/cgroup").ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("0::").map(str::to_string))
+}
+
+fn write(path: &Path, value: &str) -> Result<(), String> {
+    fs::write(path, value).map_err(|e| format!("{:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::GovernorState;
+
+    fn fake_tree() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("cartesian-cgroup-{}", std::process::id())).join("cartesian");
+        let _ = fs::remove_dir_all(&root);
+        for group in ["inference", "indexing", "vision"] {
+            fs::create_dir_all(root.join(group)).unwrap();
+        }
+        root
+    }
+
+    fn read(path: PathBuf) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn limits_follow_the_profile() {
+        let root = fake_tree();
+        let confinement = Confinement::at(root.clone());
+
+        confinement.apply(&EngineProfile::builtin(GovernorState::SidekickMode)).unwrap();
+        assert_eq!(read(root.join("inference/cpu.max")), "200000 100000");
+        assert_eq!(read(root.join("inference/cpu.weight")), "20");
+        assert_eq!(read(root.join("vision/cgroup.freeze")), "1");
+        assert_eq!(read(root.join("memory.high")), (6u64 << 30).to_string());
+
+        confinement.apply(&EngineProfile::builtin(GovernorState::GodMode)).unwrap();
+        assert_eq!(read(root.join("inference/cpu.max")), "max 100000");
+        assert_eq!(read(root.join("indexing/cpu.weight")), "100");
+        assert_eq!(read(root.join("vision/cgroup.freeze")), "0");
+        assert_eq!(read(root.join("memory.high")), "max");
+    }
+
+    #[test]
+    fn failed_writes_are_retried() {
+        let root = std::env::temp_dir().join(format!("cartesian-cgroup-retry-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("inference")).unwrap();
+        let confinement = Confinement::at(root.clone());
+        let potato = EngineProfile::builtin(GovernorState::PotatoMode);
+
+        // No indexing or vision groups yet
+        assert!(confinement.apply(&potato).is_err());
+        for group in ["indexing", "vision"] {
+            fs::create_dir_all(root.join(group)).unwrap();
+        }
+        confinement.apply(&potato).unwrap();
+        assert_eq!(read(root.join("vision/cgroup.freeze")), "1");
+    }
+
+    #[test]
+    fn tiny_cpu_quota_is_clamped() {
+        // The kernel rejects quotas under 1 ms
+        assert_eq!(cpu_max(Some(0.001)), "1000 100000");
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use crate::config;
+use crate::categories;
+use crate::lobotomy::AppCategory;
+use crate::privacy::glob_to_regex;
+
+/// A case-insensitive pattern: a glob (`*`, `?`; `*` also crosses `/`) or,
+/// prefixed with `re:`, a regex that must match somewhere in the text.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Pattern {
+    source: String,
+    re: Regex,
+}
+
+impl Pattern {
+    pub fn is_match(&self, text: &str) -> bool {
+        self.re.is_match(text)
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, String> {
+        let pattern = match source.strip_prefix("re:") {
+            Some(re) => re.to_string(),
+            None => glob_to_regex(&source).ok_or_else(|| format!("bad glob '{}'", source))?.as_str().to_string(),
+        };
+        let re = Regex::new(&format!("(?i){}", pattern)).map_err(|e| format!("'{}': {}", source, e))?;
+        Ok(Self { source, re })
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> String {
+        pattern.source
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.source)
+    }
+}
+
+/// All present fields must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessMatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<Pattern>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exe: Option<Pattern>, // Full executable path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmdline: Option<Pattern>, // Arguments joined by spaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ancestor: Option<Pattern>, // Name of any parent, grandparent...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flatpak: Option<Pattern>, // App id, e.g. "com.valvesoftware.Steam"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Pattern>, // systemd unit/scope, e.g. "app-gnome-blender-*.scope"
+}
+
+impl ProcessMatch {
+    pub fn matches(&self, p: &ProcessFacts) -> bool {
+        fn check(pattern: &Option<Pattern>, value: Option<&str>) -> bool {
+            pattern.as_ref().is_none_or(|re| value.is_some_and(|v| re.is_match(v)))
+        }
+
+        check(&self.name, Some(&p.name))
+            && check(&self.exe, p.exe.as_deref())
+            && check(&self.cmdline, Some(&p.cmdline))
+            && self.ancestor.as_ref().is_none_or(|re| p.ancestors.iter().any(|a| re.is_match(a)))
+            && check(&self.flatpak, p.flatpak_id())
+            && check(&self.unit, p.unit())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.exe.is_none() && self.cmdline.is_none()
+            && self.ancestor.is_none() && self.flatpak.is_none() && self.unit.is_none()
+    }
+}
+
+/// What a rule says a process is. Launchers are kept apart from the
+/// categories: they hint at a game (see the Predictor) without being one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Class {
+    Category(AppCategory),
+    Launcher(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClassRule {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32, // Higher is tried first; ties keep file order
+    pub when: ProcessMatch,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<AppCategory>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launcher: Option<String>, // Exclusive with `category`
+}
+
+impl ClassRule {
+    fn class(&self) -> Option<Class> {
+        match (&self.category, &self.launcher) {
+            (Some(category), None) => Some(Class::Category(*category)),
+            (None, Some(launcher)) => Some(Class::Launcher(launcher.clone())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RuleFile {
+    rules: Vec<ClassRule>,
+}
+
+/// Everything a rule can look at for one process.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessFacts {
+    pub name: String,
+    pub exe: Option<String>,
+    pub cmdline: String,
+    pub ancestors: Vec<String>, // Nearest first
+    pub cgroup: Option<String>, // cgroup v2 path, e.g. "/user.slice/.../app.slice/steam.service"
+}
+
+impl ProcessFacts {
+    /// Flatpak runs each app in an `app-flatpak-<id>-<n>.scope`.
+    pub fn flatpak_id(&self) -> Option<&str> {
+        let scope = self.cgroup.as_deref()?.rsplit('/').find_map(|c| c.strip_prefix("app-flatpak-"))?;
+        let id = scope.strip_suffix(".scope")?;
+        Some(id.rsplit_once('-').filter(|(_, n)| n.chars().all(|c| c.is_ascii_digit())).map_or(id, |(id, _)| id))
+    }
+
+    /// Innermost systemd unit or scope the process belongs to.
+    pub fn unit(&self) -> Option<&str> {
+        self.cgroup.as_deref()?.rsplit('/').find(|c| c.ends_with(".service") || c.ends_with(".scope"))
+    }
+}
+
+/// A guess at an unknown app's category from its name and executable path,
+/// offered to the user rather than applied.
+pub fn suggest(name: &str, exe: Option<&Path>) -> Option<AppCategory> {
+    let exe = exe.map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    config::CATEGORY_HINTS.iter()
+        .find(|(glob, _)| Pattern::try_from(glob.to_string()).is_ok_and(|p| p.is_match(name) || p.is_match(&exe)))
+        .map(|(_, category)| *category)
+}
+
+/// cgroup v2 path of a process, from `/proc/<pid>/cgroup`.
+pub fn read_cgroup(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("0::").map(str::to_string))
+}
+
+/// THE TRIAGE
+/// Prioritised rules that put a process into an AppCategory or mark it as a
+/// game launcher. Built-in rules come from the config lists; user rules in
+/// `$XDG_CONFIG_HOME/cartesian/process_rules.json` join them, ordered by priority.
+pub struct Classifier {
+    rules: Vec<ClassRule>,
+}
+
+impl Classifier {
+    pub fn builtin() -> Self {
+        let mut rules = Vec::new();
+        let mut add = |name: String, priority, when, category, launcher| {
+            rules.push(ClassRule { name, priority, when, category, launcher });
+        };
+        let pattern = |p: &str| Pattern::try_from(p.to_string()).ok();
+
+        for (launcher, glob) in config::LAUNCHERS {
+            add(format!("launcher:{}", glob), 100, ProcessMatch { name: pattern(glob), ..Default::default() }, None, Some(launcher.to_string()));
+        }
+        // Steam starts every game through `reaper SteamLaunch AppId=...`
+        add("steam-reaper".to_string(), 90, ProcessMatch { cmdline: pattern("*SteamLaunch*"), ..Default::default() }, Some(AppCategory::Game), None);
+        for (category, globs) in [
+            (AppCategory::Game, config::GAMES),
+            (AppCategory::Production, config::CREATIVE_SUITE),
+            (AppCategory::Development, config::DEV_TOOLS),
+            (AppCategory::Custom(categories::VIDEO_CALL), config::VIDEO_CALLS),
+        ] {
+            for glob in globs {
+                add(format!("{}:{}", category.name(), glob).to_lowercase(), 80, ProcessMatch { name: pattern(glob), ..Default::default() }, Some(category), None);
+            }
+        }
+        // Proton/Wine games show up as arbitrary .exe names; their parents give them away
+        for glob in config::GAME_PARENTS {
+            add(format!("child-of:{}", glob), 60, ProcessMatch { ancestor: pattern(glob), ..Default::default() }, Some(AppCategory::Game), None);
+        }
+        for (launcher, id) in config::LAUNCHER_FLATPAKS {
+            add(format!("flatpak:{}", id), 40, ProcessMatch { flatpak: pattern(id), ..Default::default() }, None, Some(launcher.to_string()));
+        }
+
+        Self::with_rules(rules)
+    }
+
+    /// Built-in rules plus the user's file, if present.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut classifier = Self::builtin();
+        if !path.exists() {
+            return Ok(classifier);
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("{:?}: {}", path, e))?;
+        let file: RuleFile = serde_json::from_str(&data).map_err(|e| format!("{:?}: {}", path, e))?;
+        validate(&file.rules).map_err(|errors| format!("{:?}: {}", path, errors.join("; ")))?;
+
+        classifier.rules.extend(file.rules);
+        Ok(Self::with_rules(classifier.rules))
+    }
+
+    /// These rules on top, in priority order with the rest (the registry's).
+    pub fn extended(&self, extra: Vec<ClassRule>) -> Self {
+        Self::with_rules(self.rules.iter().cloned().chain(extra).collect())
+    }
+
+    fn with_rules(mut rules: Vec<ClassRule>) -> Self {
+        rules.sort_by_key(|r| std::cmp::Reverse(r.priority)); // Stable: ties keep order
+        Self { rules }
+    }
+
+    /// The first matching rule's class and name.
+    pub fn classify(&self, process: &ProcessFacts) -> Option<(Class, &str)> {
+        self.rules.iter()
+            .find(|rule| rule.when.matches(process))
+            .and_then(|rule| Some((rule.class()?, rule.name.as_str())))
+    }
+}
+
+fn validate(rules: &[ClassRule]) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    for (i, rule) in rules.iter().enumerate() {
+        let label = if rule.name.is_empty() { format!("rule #{}", i + 1) } else { format!("rule '{}'", rule.name) };
+        if rule.name.is_empty() {
+            errors.push(format!("{}: missing name", label));
+        }
+        if rule.class().is_none() {
+            errors.push(format!("{}: needs exactly one of category or launcher", label));
+        }
+        if rule.launcher.as_ref().is_some_and(|l| l.is_empty()) {
+            errors.push(format!("{}: empty launcher name", label));
+        }
+        if rule.when.is_empty() {
+            errors.push(format!("{}: matches every process", label));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, ancestors: &[&str]) -> ProcessFacts {
+        ProcessFacts {
+            name: name.to_string(),
+            ancestors: ancestors.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn class_of(classifier: &Classifier, p: &ProcessFacts) -> Option<Class> {
+        classifier.classify(p).map(|(class, _)| class)
+    }
+
+    #[test]
+    fn names_match_whole_words_not_substrings() {
+        let classifier = Classifier::builtin();
+        assert_eq!(class_of(&classifier, &process("code", &[])), Some(Class::Category(AppCategory::Development)));
+        assert_eq!(class_of(&classifier, &process("vscodium-helper", &[])), None);
+        assert_eq!(class_of(&classifier, &process("steamwebhelper", &["steam"])), Some(Class::Launcher("steam".to_string())));
+    }
+
+    #[test]
+    fn proton_games_are_found_through_their_ancestors() {
+        let classifier = Classifier::builtin();
+        let game = process("eldenring.exe", &["wine64-preloader", "pressure-vessel", "reaper", "steam", "systemd"]);
+        assert_eq!(class_of(&classifier, &game), Some(Class::Category(AppCategory::Game)));
+
+        let mut reaper = process("reaper", &["steam"]);
+        reaper.cmdline = "reaper SteamLaunch AppId=1245620 -- proton waitforexitandrun".to_string();
+        assert_eq!(classifier.classify(&reaper).map(|(_, rule)| rule), Some("steam-reaper"));
+    }
+
+    #[test]
+    fn flatpak_and_unit_come_from_the_cgroup() {
+        let mut p = process("steam", &[]);
+        p.cgroup = Some("/user.slice/user-1000.slice/user@1000.service/app.slice/app-flatpak-com.valvesoftware.Steam-4242.scope".to_string());
+        assert_eq!(p.flatpak_id(), Some("com.valvesoftware.Steam"));
+        assert_eq!(p.unit(), Some("app-flatpak-com.valvesoftware.Steam-4242.scope"));
+
+        p.cgroup = Some("/user.slice/user-1000.slice/user@1000.service/app.slice/cartesian-core.service/cartesian/inference".to_string());
+        assert_eq!(p.flatpak_id(), None);
+        assert_eq!(p.unit(), Some("cartesian-core.service"));
+    }
+
+    #[test]
+    fn user_rules_take_their_place_by_priority() {
+        let rules: RuleFile = serde_json::from_str(r#"{"rules": [
+            {"name": "blender-unit", "priority": 10, "when": {"unit": "app-gnome-blender-*.scope"}, "category": "Production"},
+            {"name": "work-vm", "priority": 95, "when": {"cmdline": "re:qemu.*-name win11"}, "category": "Production"}
+        ]}"#).unwrap();
+        assert!(validate(&rules.rules).is_ok());
+        let mut all = Classifier::builtin().rules;
+        all.extend(rules.rules);
+        let classifier = Classifier::with_rules(all);
+
+        let mut vm = process("qemu-system-x86", &["reaper"]);
+        vm.cmdline = "/usr/bin/qemu-system-x86_64 -enable-kvm -name Win11".to_string();
+        assert_eq!(classifier.classify(&vm).map(|(_, rule)| rule), Some("work-vm"));
+
+        let mut blender = process("blender-4.2", &[]);
+        blender.cgroup = Some("/user.slice/app.slice/app-gnome-blender-9876.scope".to_string());
+        assert_eq!(classifier.classify(&blender).map(|(_, rule)| rule), Some("blender-unit"));
+    }
+
+    #[test]
+    fn suggestions_come_from_name_and_path() {
+        let steam_game = Path::new("/home/me/.local/share/Steam/steamapps/common/Hades/Hades");
+        assert_eq!(suggest("hades", Some(steam_game)), Some(AppCategory::Game));
+        assert_eq!(suggest("brave", Some(Path::new("/opt/brave.com/brave/brave"))), Some(AppCategory::Web));
+        assert_eq!(suggest("mystery", Some(Path::new("/opt/mystery/bin/mystery"))), None);
+    }
+
+    #[test]
+    fn rules_need_a_class_and_a_condition() {
+        let rules: RuleFile = serde_json::from_str(r#"{"rules": [
+            {"name": "both", "when": {"name": "x"}, "category": "Game", "launcher": "x"},
+            {"name": "everything", "when": {}, "category": "Web"}
+        ]}"#).unwrap();
+        assert_eq!(validate(&rules.rules).unwrap_err().len(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::config;
+use crate::hippocampus::{MemorySystem, RetrievalParams};
+use crate::archive::{self, ImportMode, PathRemap};
+use crate::categories::Categories;
+use crate::vault::Vault;
+use crate::eval;
+use crate::policy::GovernorPolicy;
+use crate::resources;
+use crate::telemetry;
+
+/// HEADLESS COMMANDS
+/// Maintenance entry points that run without opening the dashboard.
+///
+///   cartesian-core memory export <FILE> [--plaintext]
+///   cartesian-core memory import <FILE> [--replace] [--remap FROM=TO]...
+///   cartesian-core memory inspect <FILE>
+///   cartesian-core eval <FIXTURE_DIR> [--k N] [--params FILE]
+///   cartesian-core policy check [FILE]
+///   cartesian-core policy default
+///   cartesian-core replay [TRACE...] [--policy FILE] [--gap SECS]
+///   cartesian-core control <status | pause WORK | resume WORK | leash | unleash>
+pub fn run(args: &[String]) -> i32 {
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["memory", "export", file] => memory_export(file, false),
+        ["memory", "export", file, "--plaintext"] => memory_export(file, true),
+        ["memory", "import", file, flags @ ..] => memory_import(file, flags),
+        ["memory", "inspect", file] => memory_inspect(file),
+        ["eval", dir, flags @ ..] => retrieval_eval(dir, flags),
+        ["policy", "check"] => policy_check(&config::get_policy_path()),
+        ["policy", "check", file] => policy_check(file),
+        ["policy", "default"] => policy_default(),
+        ["replay", flags @ ..] => replay(flags),
+        ["control", command @ ..] if !command.is_empty() => control(&command.join(" ")),
+        _ => Err(format!("Unknown command: {}", args.join(" "))),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+/// With a Vault configured the archive is sealed with it, unless asked otherwise.
+fn memory_export(file: &str, plaintext: bool) -> Result<(), String> {
+    let vault = Vault::from_env();
+    let memory = MemorySystem::load(Path::new(&config::get_memory_path()), vault.as_ref())?;
+    let seal = vault.as_ref().filter(|_| !plaintext);
+    let manifest = memory.export_archive(Path::new(file), seal)?;
+    println!(
+        "Exported {} engrams ({} chunks, {} synapses) to {}{}",
+        manifest.engram_count, manifest.chunk_count, manifest.synapse_count, file,
+        if seal.is_some() { " (encrypted)" } else { "" }
+    );
+    Ok(())
+}
+
+/// Refused while a core answers on the control socket: it owns the store.
+fn memory_import(file: &str, flags: &[&str]) -> Result<(), String> {
+    let mut mode = ImportMode::Merge;
+    let mut remaps = Vec::new();
+
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match *flag {
+            "--replace" => mode = ImportMode::Replace,
+            "--merge" => mode = ImportMode::Merge,
+            "--remap" => {
+                let spec = flags.next().ok_or("--remap needs FROM=TO")?;
+                remaps.push(PathRemap::parse(spec).ok_or_else(|| format!("Bad remap '{}'", spec))?);
+            }
+            other => return Err(format!("Unknown flag '{}'", other)),
+        }
+    }
+
+    // The dashboard's next flush would write its own copy over the import
+    let running = config::get_control_socket_path()
+        .is_ok_and(|socket| resources::request(Path::new(&socket), "status").is_ok());
+    if running {
+        return Err("cartesian-core is running; close it before importing into its store".to_string());
+    }
+
+    let vault = Vault::from_env();
+    let store = config::get_memory_path();
+    let mut memory = MemorySystem::load(Path::new(&store), vault.as_ref())?;
+    let report = memory.import_archive(Path::new(file), vault.as_ref(), &remaps, mode)?;
+    memory.save(Path::new(&store), vault.as_ref())?;
+
+    println!(
+        "Imported {}: {} added, {} merged, {} skipped (sensitive)",
+        file, report.added, report.merged, report.skipped_sensitive
+    );
+    Ok(())
+}
+
+fn memory_inspect(file: &str) -> Result<(), String> {
+    let data = archive::read(Path::new(file), Vault::from_env().as_ref())?;
+    let (manifest, _) = archive::read_manifest(&data)?;
+    println!("{}", serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+fn retrieval_eval(dir: &str, flags: &[&str]) -> Result<(), String> {
+    let mut k = None;
+    let mut params = None;
+
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match *flag {
+            "--k" => {
+                let n = flags.next().ok_or("--k needs a number")?;
+                k = Some(n.parse::<usize>().map_err(|e| e.to_string())?);
+            }
+            "--params" => {
+                let file = flags.next().ok_or("--params needs a file")?;
+                let data = fs::read_to_string(file).map_err(|e| e.to_string())?;
+                params = Some(serde_json::from_str::<RetrievalParams>(&data).map_err(|e| e.to_string())?);
+            }
+            other => return Err(format!("Unknown flag '{}'", other)),
+        }
+    }
+
+    let report = eval::run(Path::new(dir), params, k)?;
+    print!("{}", report);
+    Ok(())
+}
+
+fn policy_check(file: &str) -> Result<(), String> {
+    load_categories()?;
+    let policy = GovernorPolicy::load(Path::new(file))?;
+    println!("{}: OK ({} rules, default {:?})", file, policy.rules.len(), policy.default_state);
+    Ok(())
+}
+
+fn policy_default() -> Result<(), String> {
+    println!("{}", serde_json::to_string_pretty(&GovernorPolicy::default()).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+/// Custom category names in a policy or a trace only resolve once
+/// categories.json has been read, as in the dashboard.
+fn load_categories() -> Result<(), String> {
+    Categories::load(Path::new(&config::get_categories_path())).map(|_| ())
+}
+
+/// Ask the running core to pause or resume work (WORK is capture, indexing,
+/// consolidation, generation or all), from the core's own user.
+fn control(command: &str) -> Result<(), String> {
+    let reply = resources::request(Path::new(&config::get_control_socket_path()?), command)?;
+    match reply.strip_prefix("ERR ") {
+        Some(e) => Err(e.to_string()),
+        None => {
+            println!("{}", reply.trim_start_matches("OK "));
+            Ok(())
+        }
+    }
+}
+
+/// Replay recorded ticks through a candidate policy (default: the live policy file).
+fn replay(flags: &[&str]) -> Result<(), String> {
+    let mut files = Vec::new();
+    let mut policy_file = None;
+    let mut gap = config::TRACE_SESSION_GAP;
+
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match *flag {
+            "--policy" => policy_file = Some(*flags.next().ok_or("--policy needs a file")?),
+            "--gap" => {
+                let secs = flags.next().ok_or("--gap needs seconds")?;
+                gap = Duration::from_secs_f32(secs.parse::<f32>().map_err(|e| e.to_string())?);
+            }
+            other if other.starts_with("--") => return Err(format!("Unknown flag '{}'", other)),
+            file => files.push(PathBuf::from(file)),
+        }
+    }
+
+    if files.is_empty() {
+        files = telemetry::trace_files(Path::new(&config::get_trace_dir()));
+    }
+    if files.is_empty() {
+        return Err(format!("No trace files in {}", config::get_trace_dir()));
+    }
+
+    load_categories()?;
+    let policy = match policy_file {
+        Some(file) => GovernorPolicy::load(Path::new(file))?,
+        None if Path::new(&config::get_policy_path()).exists() => GovernorPolicy::load(Path::new(&config::get_policy_path()))?,
+        None => GovernorPolicy::default(),
+    };
+
+    let records = telemetry::read_trace(&files)?;
+    print!("{}", telemetry::replay(&records, policy, gap));
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use std::time::Duration;
+use crate::lobotomy::AppCategory;
 
 /// GLOBAL CONFIGURATION
 /// The Single Source of Truth for Paths, Constants, and Tuning.
@@ -21,7 +22,69 @@ pub fn get_shm_path() -> String {
     }
 }
 
-pub const REGISTRY_PATH: &str = "process_registry.json";
+pub fn get_data_dir() -> String {
+    if cfg!(target_os = "windows") {
+        ".\\data\\".to_string()
+    } else if let Ok(dir) = std::env::var("XDG_DATA_HOME") {
+        format!("{}/cartesian/", dir)
+    } else {
+        format!("{}/.local/share/cartesian/", std::env::var("HOME").unwrap_or_default())
+    }
+}
+
+pub fn get_config_dir() -> String {
+    if cfg!(target_os = "windows") {
+        ".\\config\\".to_string()
+    } else if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+        format!("{}/cartesian/", dir)
+    } else {
+        format!("{}/.config/cartesian/", std::env::var("HOME").unwrap_or_default())
+    }
+}
+
+pub fn get_policy_path() -> String {
+    format!("{}governor_policy.json", get_config_dir())
+}
+
+pub fn get_process_rules_path() -> String {
+    format!("{}process_rules.json", get_config_dir())
+}
+
+pub fn get_registry_path() -> String {
+    format!("{}process_registry.json", get_config_dir())
+}
+
+pub fn get_categories_path() -> String {
+    format!("{}categories.json", get_config_dir())
+}
+
+/// Where the admin bridge reaches a running core (see resources.rs).
+/// Only in a private runtime dir: in a shared one like /tmp another user could
+/// bind the name first, or connect before the socket is locked down.
+pub fn get_control_socket_path() -> Result<String, String> {
+    if let Ok(path) = std::env::var("CARTESIAN_CONTROL_SOCKET") {
+        Ok(path)
+    } else if let Ok(dir) = std::env::var("XDG_RUNTIME_DIR") {
+        Ok(format!("{}/cartesian-core.sock", dir))
+    } else {
+        Err("XDG_RUNTIME_DIR is not set, so there is no private place for the control socket".to_string())
+    }
+}
+
+pub fn get_memory_path() -> String {
+    format!("{}hippocampus.bin", get_data_dir())
+}
+
+pub fn get_trace_dir() -> String {
+    format!("{}trace", get_data_dir())
+}
+
+// Before the registry moved to the config dir it lived in the working directory
+pub const LEGACY_REGISTRY_PATH: &str = "process_registry.json";
+// Shared defaults (e.g. a team's registry); the user's file overrides them per app
+pub const SYSTEM_REGISTRY_PATH: &str = "/usr/share/cartesian/process_registry.json";
+// Registry entries with a match beat the built-in name rules, not launchers
+pub const REGISTRY_RULE_PRIORITY: i32 = 85;
 
 // --- MODELS ---
 
@@ -35,20 +98,163 @@ pub const MODEL_PROJECTOR: &str = "mmproj-model-f16-4B.gguf";
 // Embedding model for memory
 pub const MODEL_EMBEDDING: &str = "all-MiniLM-L6-v2.safetensors"; 
 
+// GGUF size -> resident size (KV cache, activations, CUDA context)
+pub const VRAM_MODEL_OVERHEAD: f32 = 1.25;
+
+// --- PRIVACY ---
+
+// Keyring entry (Secret Service) holding the Hippocampus encryption key.
+pub const KEYRING_SERVICE: &str = "cartesian-hippocampus";
+
+// Directories that are never indexed. "~" expands to $HOME.
+pub const SENSITIVE_PATHS: &[&str] = &[
+    "~/.ssh", "~/.gnupg", "~/.password-store", "~/.aws", "~/.azure",
+    "~/.kube", "~/.docker", "~/.local/share/keyrings", "~/.mozilla",
+    "~/.config/google-chrome", "~/.config/chromium", "~/.config/gh",
+    "/etc/shadow", "/etc/gshadow", "/etc/ssl/private"
+];
+
+// File name globs that are never indexed.
+pub const SENSITIVE_PATTERNS: &[&str] = &[
+    "id_rsa*", "id_dsa*", "id_ecdsa*", "id_ed25519*", "*.pem", "*.key",
+    "*.p12", "*.pfx", "*.kdbx", "*.gpg", ".env", ".env.*", ".netrc",
+    ".pgpass", "credentials*", "*secret*", "*.keystore"
+];
+
 // --- TUNING ---
 pub const TICK_RATE: Duration = Duration::from_millis(500);
-pub const GOVERNOR_HYSTERESIS: Duration = Duration::from_secs(30);
+// Governor dwell times: a new target must hold this long before it is committed.
+pub const GOVERNOR_UPGRADE_DWELL: Duration = Duration::from_secs(30);   // Sustained headroom
+pub const GOVERNOR_DOWNGRADE_DWELL: Duration = Duration::from_secs(5);  // Debounce blips
+pub const GOVERNOR_EMERGENCY_DWELL: Duration = Duration::ZERO;          // Into Sidekick/Potato
+pub const GOVERNOR_HOLD_STEP: Duration = Duration::from_secs(3600);     // Per press of a hold button
+pub const GOVERNOR_HOLD_MAX: Duration = Duration::from_secs(8 * 3600);
+
+// --- WITNESS MEMORY ---
+pub const VISUAL_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+pub const VISUAL_THROTTLED_INTERVAL: Duration = Duration::from_secs(60); // BackgroundBudget::Throttled
+pub const VISUAL_CHANGE_THRESHOLD: f32 = 0.08; // Mean luma delta (0-1) between 16x16 signatures
+pub const VISUAL_THUMBNAIL_SIZE: u32 = 320;
+pub const VISUAL_ENGRAM_LIMIT: usize = 500;
+
+// --- HIPPOCAMPUS WORKER ---
+pub const MEMORY_WORKER_TICK: Duration = Duration::from_secs(1);  // LTP batches are applied at this rate
+pub const MEMORY_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+pub const MEMORY_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10); // Final flush on exit
+pub const INDEX_THROTTLE_DELAY: Duration = Duration::from_millis(500); // Between files when throttled
+pub const MAX_QUEUED_VISUALS: usize = 32;                          // Frames held while indexing is paused; oldest go first
+
+// --- RESOURCE CONTROL ---
+pub const THROTTLED_NICE: i32 = 10;                              // Worker threads on a throttled budget
+pub const CONTROL_TIMEOUT: Duration = Duration::from_secs(2);    // Per control-socket request
+
+// --- AUDIO ---
+pub const AUDIO_DUCK_LEVEL: f32 = 0.2; // Music volume factor while ducked
+// Streams from these (process binary or application name, lowercase) count as music
+pub const MUSIC_PLAYERS: &[&str] = &[
+    "spotify", "rhythmbox", "lollypop", "amberol", "elisa", "strawberry", "clementine",
+    "audacious", "deadbeef", "cmus", "mpd", "tauon", "quodlibet", "g4music",
+];
+
+// --- COMPOSITOR ---
+pub const HYPRLAND_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+pub const HYPRLAND_QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+// --- GOVERNOR TRACE ---
+pub const TRACE_MAX_BYTES: u64 = 16 * 1024 * 1024; // ~6 h of ticks per file
+pub const TRACE_KEEP: usize = 4;                    // Rotated files kept
+pub const TRACE_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+pub const TRACE_SESSION_GAP: Duration = Duration::from_secs(10); // Longer gaps split replay sessions
+
+// --- PREDICTION ---
+pub const PREDICT_HORIZON: Duration = Duration::from_secs(120); // "A game starts within..."
+pub const PREDICT_MIN_SUPPORT: u32 = 240;                        // Ticks (~2 min) before a feature counts
 
 // --- HEURISTICS ---
+// Game library scanning (see library.rs), relative to $HOME: native and Flatpak installs
+pub const STEAM_ROOTS: &[&str] = &[
+    ".local/share/Steam", ".steam/steam", ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+];
+pub const LUTRIS_DBS: &[&str] = &[
+    ".local/share/lutris/pga.db", ".var/app/net.lutris.Lutris/data/lutris/pga.db",
+];
+pub const HEROIC_DIRS: &[&str] = &[
+    ".config/heroic", ".var/app/com.heroicgameslauncher.hgl/config/heroic",
+];
+// Below the built-in name rules (Blender from Steam is still Production) and the registry
+pub const LIBRARY_RULE_PRIORITY: i32 = 70;
+pub const LIBRARY_RESCAN_INTERVAL: Duration = Duration::from_secs(300);
+// What starts Wine/Proton games: Steam's reaper, Wine itself, Lutris, Heroic and its helpers
+pub const WINE_LAUNCH_ANCESTORS: &str = "re:^(reaper|wine.*|proton|umu-run|lutris|heroic|legendary|gogdl|nile)$";
+
+// Built-in classification rules (see classify.rs). Process names are globs
+// over the whole name, compared case-insensitively.
 pub const GAMES: &[&str] = &[
-    "steam", "lutris", "heroic", "wineserver", "gamescope", 
+    "wineserver", "gamescope", 
     "yuzu", "ryujinx", "dota2", "cs2", "factorio"
 ];
 
+// Launchers idle in the tray for hours; they hint at a game rather than being one.
+// (signal name, process name glob)
+pub const LAUNCHERS: &[(&str, &str)] = &[
+    ("steam", "steam"), ("steam", "steamwebhelper"),
+    ("lutris", "lutris"),
+    ("heroic", "heroic"), ("heroic", "legendary"), ("heroic", "gogdl"),
+];
+
+// (signal name, Flatpak app id). Below the ancestry rules, since a Flatpak
+// launcher's games run in the launcher's scope.
+pub const LAUNCHER_FLATPAKS: &[(&str, &str)] = &[
+    ("steam", "com.valvesoftware.Steam"),
+    ("lutris", "net.lutris.Lutris"),
+    ("heroic", "com.heroicgameslauncher.hgl"),
+];
+
+// Anything started below these is a game (Proton/Wine titles have arbitrary .exe names).
+pub const GAME_PARENTS: &[&str] = &[
+    "reaper", "lutris", "lutris-wrapper*", "legendary", "gogdl"
+];
+
 pub const CREATIVE_SUITE: &[&str] = &[
-    "blender", "resolve", "obs", "gimp", "krita", "godot", "unity"
+    "blender", "resolve", "obs", "gimp*", "krita", "godot*", "unity"
+];
+
+// Filed under the VideoCall category (see categories.rs)
+pub const VIDEO_CALLS: &[&str] = &[
+    "zoom", "zoom.real", "teams-for-linux", "webex*", "skypeforlinux"
+];
+
+// Hints for suggesting a category for an unknown app, first match wins.
+// Globs over the process name or the executable path.
+pub const CATEGORY_HINTS: &[(&str, AppCategory)] = &[
+    ("*/steamapps/*", AppCategory::Game), ("*/games/*", AppCategory::Game), ("*.exe", AppCategory::Game),
+    ("*/jetbrains/*", AppCategory::Development), ("*/.cargo/bin/*", AppCategory::Development),
+    ("*/node_modules/*", AppCategory::Development), ("*studio*", AppCategory::Development),
+    ("*chrom*", AppCategory::Web), ("*browser*", AppCategory::Web), ("*brave*", AppCategory::Web),
+    ("*vivaldi*", AppCategory::Web), ("*opera*", AppCategory::Web), ("*librewolf*", AppCategory::Web),
+    ("*vlc*", AppCategory::Media), ("*mpv*", AppCategory::Media), ("*spotify*", AppCategory::Media),
+    ("*player*", AppCategory::Media),
+    ("*darktable*", AppCategory::Production), ("*inkscape*", AppCategory::Production),
+    ("*kdenlive*", AppCategory::Production), ("*audacity*", AppCategory::Production),
+    ("/usr/lib/*", AppCategory::System), ("/usr/libexec/*", AppCategory::System),
 ];
+// Context dominance: score = weighted shares among busy categories (+ focus).
+pub const CONTEXT_IDLE_CPU: f32 = 2.0;        // Per-core %; quieter categories without GPU use or focus are idle
+pub const CONTEXT_WEIGHT_CPU: f32 = 0.35;
+pub const CONTEXT_WEIGHT_MEMORY: f32 = 0.15;
+pub const CONTEXT_WEIGHT_GPU: f32 = 0.3;
+pub const CONTEXT_WEIGHT_FOCUS: f32 = 0.3;
+pub const CONTEXT_SMOOTHING: f32 = 0.3;       // Per scan; a closed app fades out in a few seconds
+pub const CONTEXT_MIN_SCORE: f32 = 0.1;       // Below this a category no longer counts as active
+// Between rescans only process events and the processes that count are read
+pub const PROC_RESCAN_INTERVAL: Duration = Duration::from_secs(30);
+// Unknown apps smaller than this (all processes together) aren't worth asking about
+pub const UNKNOWN_APP_MIN_BYTES: u64 = 50_000_000;
+pub const UNKNOWN_APPS_SHOWN: usize = 5;
+// An unclassified fullscreen window only counts as a Game while it holds this much VRAM
+pub const FULLSCREEN_GAME_MIN_GPU_BYTES: u64 = 512 * 1024 * 1024;
 
 pub const DEV_TOOLS: &[&str] = &[
-    "code", "zed", "nvim", "alacritty", "cargo", "powershell", "cmd"
+    "code", "code-oss", "codium", "zed", "zeditor", "nvim", "alacritty",
+    "cargo", "powershell.exe", "pwsh", "cmd.exe"
 ];
\ No newline at end of file
@@ -0,0 +1,389 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::accounting::{self, Resident, Subsystem};
+use crate::cgroup::{self, Group};
+use crate::config;
+use crate::hippocampus::MemorySystem;
+use crate::inference::{BackgroundBudget, Embedder, EmbeddingEngine};
+use crate::resources::{self, Work};
+use crate::vault::Vault;
+use crate::witness::VisualObservation;
+
+enum Job {
+    Index(PathBuf),
+    Visual(VisualObservation),
+    Reinforce(Vec<(PathBuf, usize)>),
+    Shutdown(Sender<()>),
+}
+
+/// THE CONSOLIDATION WORKER
+/// Thread-safe front door to the Hippocampus.
+///
+/// 1. Readers share a lock and never wait on indexing: reading, chunking and
+///    embedding happen on the worker under a shared lock, only the final insert is exclusive.
+/// 2. LTP reinforcement from recalls is queued and applied in batches.
+/// 3. The store is flushed to disk in the background whenever it changed.
+/// 4. Indexing follows the Governor's BackgroundBudget: queued files wait while
+///    paused and trickle in while throttled.
+/// 5. The ResourceController can hold indexing (files and visual engrams) and
+///    consolidation (LTP) on their own; held work queues up. Flushes never stop.
+///
+/// Cheap to clone; every clone talks to the same worker.
+#[derive(Clone)]
+pub struct MemoryHandle {
+    memory: Arc<RwLock<MemorySystem>>,
+    embedder: Arc<EmbeddingEngine>,
+    jobs: Sender<Job>,
+    pending: Arc<AtomicUsize>,
+    budget: Arc<AtomicU8>,
+}
+
+impl MemoryHandle {
+    /// Move the memory onto a background worker that persists to `store` (sealed if a Vault is given).
+    /// Without a `store` nothing is ever written to disk.
+    pub fn spawn(memory: MemorySystem, embedder: EmbeddingEngine, store: Option<PathBuf>, vault: Option<Vault>) -> Self {
+        let (jobs, rx) = mpsc::channel();
+        let handle = Self {
+            memory: Arc::new(RwLock::new(memory)),
+            embedder: Arc::new(embedder),
+            jobs,
+            pending: Arc::new(AtomicUsize::new(0)),
+            budget: Arc::new(AtomicU8::new(BackgroundBudget::Full as u8)),
+        };
+
+        let worker = handle.clone_internals();
+        thread::Builder::new()
+            .name("cartesian-memory".to_string())
+            .spawn(move || worker.run(rx, store, vault))
+            .expect("Hippocampus: Failed to spawn worker thread");
+
+        handle
+    }
+
+    // --- WRITES (queued) ---
+
+    /// Queue a file or a whole directory tree for indexing.
+    pub fn index(&self, path: PathBuf) {
+        self.enqueue(Job::Index(path));
+    }
+
+    /// Queue a described screen frame as a visual engram.
+    pub fn remember_visual(&self, observation: VisualObservation) {
+        self.enqueue(Job::Visual(observation));
+    }
+
+    // --- READS (concurrent) ---
+
+    /// Retrieve context for a query. Blocks only for the duration of the scan;
+    /// the LTP update is handed to the worker.
+    pub fn retrieve_context(&self, query_text: &str) -> Vec<String> {
+        let query_vec = self.embedder.embed(query_text);
+        let hits = match self.memory.read() {
+            Ok(memory) => memory.recall(query_text, &query_vec),
+            Err(_) => return Vec::new(),
+        };
+
+        let reinforce: Vec<(PathBuf, usize)> = hits.iter().map(|h| (h.path.clone(), h.chunk_id)).collect();
+        if !reinforce.is_empty() {
+            let _ = self.jobs.send(Job::Reinforce(reinforce));
+        }
+        hits.into_iter().map(|h| h.text).collect()
+    }
+
+    /// `retrieve_context` for iced tasks and tool calls: the scan runs on a blocking thread.
+    pub async fn retrieve_context_async(self, query_text: String) -> Vec<String> {
+        tokio::task::spawn_blocking(move || self.retrieve_context(&query_text))
+            .await
+            .unwrap_or_default()
+    }
+
+    pub fn engram_count(&self) -> usize {
+        self.memory.read().map(|m| m.engram_count()).unwrap_or(0)
+    }
+
+    /// Memory held by the embedder the worker shares.
+    pub fn embedder_resident(&self) -> Resident {
+        self.embedder.resident()
+    }
+
+    pub fn set_background(&self, budget: BackgroundBudget) {
+        self.budget.store(budget as u8, Ordering::Relaxed);
+    }
+
+    /// Files and visual jobs waiting on the worker.
+    pub fn pending_jobs(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Apply queued LTP, flush the store and stop the worker, waiting up to
+    /// MEMORY_SHUTDOWN_TIMEOUT. Files still queued for indexing are dropped.
+    pub fn shutdown(&self) {
+        let (done, flushed) = mpsc::channel();
+        if self.jobs.send(Job::Shutdown(done)).is_ok() && flushed.recv_timeout(config::MEMORY_SHUTDOWN_TIMEOUT).is_err() {
+            eprintln!("Hippocampus: Worker didn't flush in time, recent changes may be lost.");
+        }
+    }
+
+    fn enqueue(&self, job: Job) {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        if self.jobs.send(job).is_err() {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            eprintln!("Hippocampus: Worker is gone, job dropped.");
+        }
+    }
+
+    fn clone_internals(&self) -> Worker {
+        Worker {
+            memory: self.memory.clone(),
+            embedder: self.embedder.clone(),
+            pending: self.pending.clone(),
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+// --- THE WORKER THREAD ---
+
+struct Worker {
+    memory: Arc<RwLock<MemorySystem>>,
+    embedder: Arc<EmbeddingEngine>,
+    pending: Arc<AtomicUsize>,
+    budget: Arc<AtomicU8>,
+}
+
+impl Worker {
+    fn run(self, rx: Receiver<Job>, store: Option<PathBuf>, vault: Option<Vault>) {
+        cgroup::join(Group::Indexing);
+        let brakes = resources::controller();
+        brakes.join(Work::Indexing);
+        let mut ltp_batch: Vec<(PathBuf, usize)> = Vec::new();
+        let mut backlog: VecDeque<PathBuf> = VecDeque::new();
+        let mut visuals: VecDeque<VisualObservation> = VecDeque::new();
+        let mut saved_revision = 0;
+        let mut last_flush = Instant::now();
+        let mut last_index = Instant::now();
+
+        loop {
+            let budget = self.budget();
+            // Don't sleep while there is indexing we are allowed to do
+            let wait = match budget {
+                _ if backlog.is_empty() || brakes.is_paused(Work::Indexing) => config::MEMORY_WORKER_TICK,
+                BackgroundBudget::Full => Duration::ZERO,
+                BackgroundBudget::Throttled => config::INDEX_THROTTLE_DELAY.saturating_sub(last_index.elapsed()),
+                BackgroundBudget::Paused => config::MEMORY_WORKER_TICK,
+            };
+
+            match rx.recv_timeout(wait) {
+                Ok(Job::Index(path)) => {
+                    // The job becomes one pending entry per file
+                    let before = backlog.len();
+                    collect_files(&path, &mut backlog);
+                    self.pending.fetch_add(backlog.len() - before, Ordering::Relaxed);
+                    self.pending.fetch_sub(1, Ordering::Relaxed);
+                }
+                Ok(Job::Visual(obs)) => {
+                    // Thumbnails are big; a long pause keeps only the latest frames
+                    if visuals.len() >= config::MAX_QUEUED_VISUALS {
+                        visuals.pop_front();
+                        self.pending.fetch_sub(1, Ordering::Relaxed);
+                    }
+                    visuals.push_back(obs);
+                }
+                Ok(Job::Reinforce(hits)) => ltp_batch.extend(hits),
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(Job::Shutdown(done)) => {
+                    self.apply_ltp(&mut ltp_batch);
+                    self.flush(store.as_deref(), vault.as_ref(), &mut saved_revision);
+                    let _ = done.send(());
+                    return;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.apply_ltp(&mut ltp_batch);
+                    self.flush(store.as_deref(), vault.as_ref(), &mut saved_revision);
+                    return;
+                }
+            }
+
+            if !brakes.is_paused(Work::Indexing) {
+                while let Some(obs) = visuals.pop_front() {
+                    self.index_visual(obs);
+                    self.pending.fetch_sub(1, Ordering::Relaxed);
+                }
+            }
+
+            let may_index = !brakes.is_paused(Work::Indexing) && match self.budget() {
+                BackgroundBudget::Full => true,
+                BackgroundBudget::Throttled => last_index.elapsed() >= config::INDEX_THROTTLE_DELAY,
+                BackgroundBudget::Paused => false,
+            };
+            if may_index {
+                if let Some(file) = backlog.pop_front() {
+                    self.index_file(&file);
+                    self.pending.fetch_sub(1, Ordering::Relaxed);
+                    last_index = Instant::now();
+                }
+            }
+
+            // Batch LTP: one exclusive lock for many recalls. Held while paused,
+            // but flushes go on: Potato Mode is when the process is likeliest to be killed.
+            let may_consolidate = !brakes.is_paused(Work::Consolidation);
+            if may_consolidate && (self.pending.load(Ordering::Relaxed) == 0 || ltp_batch.len() > 256) {
+                self.apply_ltp(&mut ltp_batch);
+            }
+
+            if last_flush.elapsed() >= config::MEMORY_FLUSH_INTERVAL {
+                self.flush(store.as_deref(), vault.as_ref(), &mut saved_revision);
+                last_flush = Instant::now();
+            }
+        }
+    }
+
+    fn budget(&self) -> BackgroundBudget {
+        match self.budget.load(Ordering::Relaxed) {
+            b if b == BackgroundBudget::Paused as u8 => BackgroundBudget::Paused,
+            b if b == BackgroundBudget::Throttled as u8 => BackgroundBudget::Throttled,
+            _ => BackgroundBudget::Full,
+        }
+    }
+
+    /// Files are committed one at a time so readers interleave with a large tree.
+    fn index_file(&self, path: &Path) {
+        let _span = accounting::enter(Subsystem::Indexing);
+        let prepared = match self.memory.read() {
+            Ok(memory) => memory.prepare_file(path.to_path_buf(), self.embedder.as_ref()),
+            Err(_) => return,
+        };
+        match prepared {
+            Ok(engram) => {
+                if let Ok(mut memory) = self.memory.write() {
+                    memory.commit(engram);
+                }
+            }
+            Err(e) => eprintln!("Hippocampus: Skipped {:?}: {}", path, e),
+        }
+    }
+
+    fn index_visual(&self, obs: VisualObservation) {
+        let _span = accounting::enter(Subsystem::Indexing);
+        let prepared = match self.memory.read() {
+            Ok(memory) => memory.prepare_visual(&obs.description, obs.thumbnail, obs.captured_at, self.embedder.as_ref()),
+            Err(_) => return,
+        };
+        match prepared {
+            Ok(engram) => {
+                if let Ok(mut memory) = self.memory.write() {
+                    memory.commit(engram);
+                }
+            }
+            Err(e) => eprintln!("Hippocampus: Failed to store visual engram: {}", e),
+        }
+    }
+
+    fn apply_ltp(&self, batch: &mut Vec<(PathBuf, usize)>) {
+        if batch.is_empty() { return; }
+        if let Ok(mut memory) = self.memory.write() {
+            memory.reinforce_batch(batch);
+        }
+        batch.clear();
+    }
+
+    /// Serialize under a shared lock so readers keep going while the store is written.
+    fn flush(&self, store: Option<&Path>, vault: Option<&Vault>, saved_revision: &mut u64) {
+        let Some(store) = store else { return; };
+        let Ok(memory) = self.memory.read() else { return; };
+        let revision = memory.revision();
+        if revision == *saved_revision { return; }
+
+        match memory.save(store, vault) {
+            Ok(()) => *saved_revision = revision,
+            Err(e) => eprintln!("Hippocampus: Failed to save store: {}", e),
+        }
+    }
+}
+
+/// Symlinks inside a tree are not followed: they can loop, or lead out of it.
+fn collect_files(path: &Path, out: &mut VecDeque<PathBuf>) {
+    if path.is_dir() {
+        collect_dir(path, out);
+    } else {
+        out.push_back(path.to_path_buf());
+    }
+}
+
+fn collect_dir(dir: &Path, out: &mut VecDeque<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return; };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Build output and VCS internals are noise
+        if name.starts_with('.') || name == "target" || name == "node_modules" {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => collect_dir(&entry.path(), out),
+            Ok(kind) if kind.is_file() => out.push_back(entry.path()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cartesian-consolidation-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn trees_are_walked_without_following_symlinks() {
+        let dir = scratch("walk");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::write(dir.join("a.md"), "a").unwrap();
+        fs::write(dir.join("sub/b.md"), "b").unwrap();
+        fs::write(dir.join(".git/config"), "c").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("a.md"), dir.join("link.md")).unwrap();
+
+        let mut found = VecDeque::new();
+        collect_files(&dir, &mut found);
+        let mut found: Vec<PathBuf> = found.into();
+        found.sort();
+        assert_eq!(found, vec![dir.join("a.md"), dir.join("sub/b.md")]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn shutdown_flushes_what_was_indexed() {
+        let dir = scratch("shutdown");
+        let note = dir.join("note.md");
+        fs::write(&note, "The quarterly budget review moved to the second Thursday of the month.").unwrap();
+        let store = dir.join("hippocampus.bin");
+
+        let memory = MemoryHandle::spawn(MemorySystem::new(), EmbeddingEngine::new(), Some(store.clone()), None);
+        memory.index(note.clone());
+        memory.shutdown();
+        assert_eq!(MemorySystem::load(&store, None).unwrap().engram_count(), 1);
+
+        // Without a store (it failed to load) nothing is written
+        let _ = fs::remove_file(&store);
+        let memory = MemoryHandle::spawn(MemorySystem::new(), EmbeddingEngine::new(), None, None);
+        memory.index(note);
+        memory.shutdown();
+        assert_eq!(memory.engram_count(), 1);
+        assert!(!store.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
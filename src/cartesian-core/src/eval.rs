@@ -0,0 +1,213 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use crate::hippocampus::{MemorySystem, RetrievalParams};
+use crate::inference::Embedder;
+
+/// THE EVAL HARNESS
+/// Measures Hippocampus retrieval quality against a labelled fixture, so
+/// thresholds can be tuned with numbers instead of guesswork.
+///
+/// Fixture Layout:
+///   <dir>/corpus/**   Files to index
+///   <dir>/eval.json   { "k": 5, "params": { ... }, "queries": [
+///                       { "query": "...", "expected": [{ "file": "a.md", "contains": "..." }] } ] }
+///
+/// A hit is relevant when its path ends with `file` and (if given) its text contains `contains`.
+#[derive(Debug, Deserialize)]
+pub struct EvalSpec {
+    #[serde(default = "default_k")]
+    pub k: usize,
+    #[serde(default)]
+    pub params: Option<RetrievalParams>,
+    pub queries: Vec<EvalQuery>,
+}
+
+fn default_k() -> usize { 5 }
+
+#[derive(Debug, Deserialize)]
+pub struct EvalQuery {
+    pub query: String,
+    pub expected: Vec<Expected>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Expected {
+    pub file: String,
+    #[serde(default)]
+    pub contains: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct EvalReport {
+    pub k: usize,
+    pub queries: usize,
+    pub recall_at_k: f32,
+    pub mrr: f32,
+    pub mean_latency: Duration,
+    pub p95_latency: Duration,
+    pub misses: Vec<String>, // Queries that found none of their expected chunks
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "queries:      {}", self.queries)?;
+        writeln!(f, "recall@{}:     {:.3}", self.k, self.recall_at_k)?;
+        writeln!(f, "MRR:          {:.3}", self.mrr)?;
+        writeln!(f, "latency mean: {:?}", self.mean_latency)?;
+        writeln!(f, "latency p95:  {:?}", self.p95_latency)?;
+        for miss in &self.misses {
+            writeln!(f, "MISS: {}", miss)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic stand-in for all-MiniLM-L6-v2: a hashed bag of words.
+/// Related texts share words, so cosine similarity still tracks topical overlap.
+pub struct HashEmbedder {
+    dims: usize,
+}
+
+impl HashEmbedder {
+    pub fn new() -> Self {
+        Self { dims: 384 }
+    }
+}
+
+impl Embedder for HashEmbedder {
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut v = vec![0.0; self.dims];
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| w.len() > 2) {
+            // FNV-1a
+            let mut hash: u64 = 0xcbf29ce484222325;
+            for b in word.to_lowercase().bytes() {
+                hash ^= b as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+            v[(hash % self.dims as u64) as usize] += 1.0;
+        }
+        v
+    }
+}
+
+/// Index the fixture corpus with the stub embedder and score every query.
+/// `params` and `k` override the values in eval.json.
+pub fn run(dir: &Path, params: Option<RetrievalParams>, k: Option<usize>) -> Result<EvalReport, String> {
+    let spec_data = fs::read_to_string(dir.join("eval.json")).map_err(|e| e.to_string())?;
+    let spec: EvalSpec = serde_json::from_str(&spec_data).map_err(|e| e.to_string())?;
+    let k = k.unwrap_or(spec.k);
+
+    let embedder = HashEmbedder::new();
+    let mut memory = MemorySystem::new();
+    memory.set_params(params.or(spec.params).unwrap_or_default());
+
+    let mut files = Vec::new();
+    collect_files(&dir.join("corpus"), &mut files)?;
+    files.sort();
+    for file in files {
+        memory.index_file(file, &embedder)?;
+    }
+
+    let mut recall_sum = 0.0;
+    let mut rr_sum = 0.0;
+    let mut latencies = Vec::new();
+    let mut misses = Vec::new();
+
+    for q in &spec.queries {
+        let query_vec = embedder.embed(&q.query);
+        let started = Instant::now();
+        let hits = memory.recall(&q.query, &query_vec);
+        latencies.push(started.elapsed());
+
+        let top = &hits[..hits.len().min(k)];
+        let is_relevant = |hit_path: &Path, text: &str, e: &Expected| {
+            hit_path.ends_with(&e.file) && e.contains.as_ref().is_none_or(|c| text.contains(c.as_str()))
+        };
+
+        let found = q.expected.iter()
+            .filter(|e| top.iter().any(|h| is_relevant(&h.path, &h.text, e)))
+            .count();
+        if !q.expected.is_empty() {
+            recall_sum += found as f32 / q.expected.len() as f32;
+        }
+        if found == 0 {
+            misses.push(q.query.clone());
+        }
+
+        if let Some(rank) = hits.iter().position(|h| q.expected.iter().any(|e| is_relevant(&h.path, &h.text, e))) {
+            rr_sum += 1.0 / (rank + 1) as f32;
+        }
+    }
+
+    let n = spec.queries.len().max(1);
+    latencies.sort();
+    let mean_latency = latencies.iter().sum::<Duration>() / n as u32;
+    let p95_latency = latencies.get((latencies.len() * 95 / 100).min(latencies.len().saturating_sub(1)))
+        .copied()
+        .unwrap_or_default();
+
+    Ok(EvalReport {
+        k,
+        queries: spec.queries.len(),
+        recall_at_k: recall_sum / n as f32,
+        mrr: rr_sum / n as f32,
+        mean_latency,
+        p95_latency,
+        misses,
+    })
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|e| format!("{:?}: {}", dir, e))? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            collect_files(&path, out)?;
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/retrieval")
+    }
+
+    /// The stub embedder is a bag of words: paraphrases score far below the
+    /// MiniLM similarities the default thresholds are tuned for (recall@3 is
+    /// 0.1 at the defaults). So the fixture lowers the two similarity
+    /// thresholds and nothing else; every other parameter is gated as shipped.
+    #[test]
+    fn fixture_only_lowers_the_similarity_thresholds() {
+        let spec: EvalSpec = serde_json::from_str(&fs::read_to_string(fixture().join("eval.json")).unwrap()).unwrap();
+        let params = spec.params.unwrap();
+        let defaults = RetrievalParams::default();
+        assert!(params.vector_threshold < defaults.vector_threshold);
+        assert!(params.selection_threshold < defaults.selection_threshold);
+        assert_eq!(
+            RetrievalParams { vector_threshold: defaults.vector_threshold, selection_threshold: defaults.selection_threshold, ..params },
+            defaults,
+        );
+    }
+
+    #[test]
+    fn bundled_fixture_meets_baseline() {
+        let report = run(&fixture(), None, None).unwrap();
+        assert!(report.recall_at_k >= 0.8, "recall regressed:\n{}", report);
+        assert!(report.mrr >= 0.7, "MRR regressed:\n{}", report);
+    }
+
+    #[test]
+    fn stub_embedder_is_deterministic() {
+        let embedder = HashEmbedder::new();
+        assert_eq!(embedder.embed("governor hysteresis"), embedder.embed("governor hysteresis"));
+        assert_ne!(embedder.embed("governor hysteresis"), embedder.embed("audio mixer"));
+    }
+}
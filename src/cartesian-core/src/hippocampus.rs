@@ -1,10 +1,14 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use serde::{Serialize, Deserialize};
 use std::io::Cursor;
-// Import the CPU-based Embedding Engine from inference.rs
-use crate::inference::EmbeddingEngine;
+// Embedders: the CPU-based EmbeddingEngine from inference.rs, or a test stub
+use crate::inference::Embedder;
+use crate::privacy::PrivacyFilter;
+use crate::vault::Vault;
+use crate::archive::{self, ArchiveManifest, ImportMode, ImportReport, PathRemap};
+use crate::config;
 
 /// THE HIPPOCAMPUS (v2.1)
 /// A Biomimetic, Compressed, Multimodal Memory System.
@@ -14,6 +18,8 @@ use crate::inference::EmbeddingEngine;
 /// 2. Spreading Activation (Synapses) for associative recall.
 /// 3. Long-Term Potentiation (LTP) for learning user habits.
 /// 4. Z-Layer (zstd) for storage optimization (<5% overhead).
+/// 5. Privacy Filter + Vault: sensitive paths are refused, secrets are
+///    redacted before embedding, and the store can be sealed at rest.
 
 // --- DATA STRUCTURES ---
 
@@ -22,6 +28,7 @@ pub enum FileType {
     Code,   // Indentation-based chunking
     Prose,  // Paragraph-based chunking
     Config, // Line-based chunking
+    Visual, // Witness frame: OCR text stored in the skeleton
     Unknown,
 }
 
@@ -67,6 +74,48 @@ pub struct Engram {
     
     // The Chunks
     pub chunks: Vec<Chunk>,
+
+    // VISUAL ENGRAMS ONLY
+    // Capture time (Unix seconds) and a downscaled PNG of the frame
+    #[serde(default)]
+    pub captured_at: Option<u64>,
+    #[serde(default)]
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// Retrieval tuning knobs. Defaults are the hand-picked v2.1 values;
+/// use the eval harness (`cartesian-core eval`) before changing them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrievalParams {
+    pub entity_score: f32,          // Base activation of an exact entity hit
+    pub vector_threshold: f32,      // Minimum (similarity * LTP) to activate a chunk
+    pub ltp_rate: f32,              // LTP multiplier gained per recall
+    pub propagation_threshold: f32, // Activation needed to fire synapses
+    pub synaptic_decay: f32,        // Signal loss per synaptic hop
+    pub selection_threshold: f32,   // Final activation needed to enter the context
+}
+
+impl Default for RetrievalParams {
+    fn default() -> Self {
+        Self {
+            entity_score: 1.5,
+            vector_threshold: 0.7,
+            ltp_rate: 0.01,
+            propagation_threshold: 0.8,
+            synaptic_decay: 0.5,
+            selection_threshold: 0.75,
+        }
+    }
+}
+
+/// A single retrieved chunk with its final activation.
+#[derive(Debug, Clone)]
+pub struct Recollection {
+    pub path: PathBuf,
+    pub chunk_id: usize,
+    pub score: f32,
+    pub text: String,
 }
 
 // --- MEMORY SYSTEM ---
@@ -78,6 +127,15 @@ pub struct MemorySystem {
     // Fast RAM Index (The Card Catalog)
     // Optimized for O(N) linear scanning
     flat_index: Vec<(PathBuf, usize, Vec<f32>)>, 
+
+    // Deny-list + secret redaction
+    privacy: PrivacyFilter,
+
+    // Bumped on every change; the worker saves when it moves
+    revision: u64,
+
+    // Retrieval thresholds
+    params: RetrievalParams,
 }
 
 impl MemorySystem {
@@ -85,12 +143,157 @@ impl MemorySystem {
         Self {
             library: HashMap::new(),
             flat_index: Vec::new(),
+            privacy: PrivacyFilter::new(),
+            revision: 0,
+            params: RetrievalParams::default(),
         }
     }
 
+    // --- PERSISTENCE (Z-Layer + Vault) ---
+
+    /// Load the library from disk. A missing file yields an empty memory.
+    /// Sealed stores require the matching Vault.
+    pub fn load(path: &Path, vault: Option<&Vault>) -> Result<Self, String> {
+        let mut memory = Self::new();
+        if !path.exists() {
+            return Ok(memory);
+        }
+
+        let raw = fs::read(path).map_err(|e| e.to_string())?;
+        let compressed = if Vault::is_sealed(&raw) {
+            let vault = vault.ok_or("Hippocampus: Store is encrypted but no key is configured.")?;
+            vault.open(&raw)?
+        } else {
+            raw
+        };
+
+        let json = zstd::decode_all(Cursor::new(compressed))
+            .map_err(|e| format!("Decompression failed: {}", e))?;
+        let engrams: Vec<Engram> = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+
+        for engram in engrams {
+            memory.insert_engram(engram);
+        }
+        memory.revision = 0;
+        Ok(memory)
+    }
+
+    /// Write the library to disk, sealing it if a Vault is given.
+    pub fn save(&self, path: &Path, vault: Option<&Vault>) -> Result<(), String> {
+        let engrams: Vec<&Engram> = self.library.values().collect();
+        let json = serde_json::to_vec(&engrams).map_err(|e| e.to_string())?;
+        let compressed = zstd::encode_all(Cursor::new(json), 3)
+            .map_err(|e| format!("Compression failed: {}", e))?;
+
+        let data = match vault {
+            Some(vault) => vault.seal(&compressed)?,
+            None if fs::read(path).is_ok_and(|raw| Vault::is_sealed(&raw)) => {
+                return Err("Hippocampus: Refusing to overwrite an encrypted store with plaintext.".to_string());
+            }
+            None => compressed,
+        };
+
+        write_atomic(path, &data)
+    }
+
+    /// Changes since load. Compare against a saved value to know if a flush is due.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn engram_count(&self) -> usize {
+        self.library.len()
+    }
+
+    // --- PORTABILITY (Archive) ---
+
+    /// Export every engram into a single archive file, sealed if a Vault is given.
+    pub fn export_archive(&self, path: &Path, vault: Option<&Vault>) -> Result<ArchiveManifest, String> {
+        let engrams: Vec<&Engram> = self.library.values().collect();
+        let data = archive::pack(&engrams)?;
+        let (manifest, _) = archive::read_manifest(&data)?;
+        match vault {
+            Some(vault) => write_atomic(path, &vault.seal(&data)?)?,
+            None => write_atomic(path, &data)?,
+        }
+        Ok(manifest)
+    }
+
+    /// Import an archive, rewriting path prefixes first. Sealed archives need the Vault.
+    /// Merge keeps local engrams on collision but folds in the archived LTP counters
+    /// (max of both) and synapses. Paths caught by the Privacy Filter are dropped.
+    pub fn import_archive(&mut self, path: &Path, vault: Option<&Vault>, remaps: &[PathRemap], mode: ImportMode) -> Result<ImportReport, String> {
+        let data = archive::read(path, vault)?;
+        let (_, engrams) = archive::unpack(&data)?;
+
+        if mode == ImportMode::Replace {
+            self.library.clear();
+            self.flat_index.clear();
+            self.revision += 1;
+        }
+
+        let mut report = ImportReport::default();
+        for mut engram in engrams {
+            engram.path = PathRemap::apply(remaps, &engram.path);
+            for synapse in &mut engram.synapses {
+                synapse.target = PathRemap::apply(remaps, &synapse.target);
+            }
+
+            if self.privacy.is_sensitive(&engram.path) {
+                report.skipped_sensitive += 1;
+                continue;
+            }
+
+            match self.library.get_mut(&engram.path) {
+                Some(local) => {
+                    if local.chunks.len() == engram.chunks.len() {
+                        for (mine, theirs) in local.chunks.iter_mut().zip(&engram.chunks) {
+                            mine.access_count = mine.access_count.max(theirs.access_count);
+                        }
+                    }
+                    for synapse in engram.synapses {
+                        if !local.synapses.iter().any(|s| s.target == synapse.target) {
+                            local.synapses.push(synapse);
+                        }
+                    }
+                    self.revision += 1;
+                    report.merged += 1;
+                }
+                None => {
+                    self.insert_engram(engram);
+                    report.added += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Insert a complete engram and register its chunks in the fast index.
+    fn insert_engram(&mut self, engram: Engram) {
+        self.flat_index.retain(|(p, _, _)| p != &engram.path);
+        for chunk in &engram.chunks {
+            self.flat_index.push((engram.path.clone(), chunk.id, chunk.embedding.clone()));
+        }
+        self.library.insert(engram.path.clone(), engram);
+        self.revision += 1;
+    }
+
     // --- PIPELINE A: INGESTION (The Lazy Indexer) ---
 
-    pub fn index_file(&mut self, path: PathBuf, embedder: &EmbeddingEngine) -> Result<(), String> {
+    pub fn index_file(&mut self, path: PathBuf, embedder: &impl Embedder) -> Result<(), String> {
+        let engram = self.prepare_file(path, embedder)?;
+        self.commit(engram);
+        Ok(())
+    }
+
+    /// Read, chunk, redact and embed a file without touching the library.
+    /// The slow half of indexing; safe to run while others are reading.
+    pub fn prepare_file(&self, path: PathBuf, embedder: &impl Embedder) -> Result<Engram, String> {
+        // 0. Privacy Gate: never read key stores, password vaults, etc.
+        if self.privacy.is_sensitive(&path) {
+            return Err(format!("Refusing to index sensitive path {:?}", path));
+        }
+
         let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
         let file_type = self.detect_type(&path);
         
@@ -100,19 +303,19 @@ impl MemorySystem {
         // 2. Vector Embedding (Real CPU Inference)
         let mut chunks = Vec::new();
         for (i, (start, end, text_slice)) in text_chunks.into_iter().enumerate() {
-            // Generate real vector using the passed EmbeddingEngine
-            let embedding = embedder.embed(&text_slice);
+            // Secrets never reach the embedder
+            let (clean_text, _) = self.privacy.redact(&text_slice);
+
+            // Generate real vector using the passed Embedder
+            let embedding = embedder.embed(&clean_text);
             
             chunks.push(Chunk {
                 id: i,
                 start_byte: start,
                 end_byte: end,
-                embedding: embedding.clone(),
+                embedding,
                 access_count: 0,
             });
-            
-            // Add to Fast Index for O(N) scanning
-            self.flat_index.push((path.clone(), i, embedding));
         }
         
         // 3. Skeletonization & Compression (Z-Layer)
@@ -130,15 +333,82 @@ impl MemorySystem {
             entity_map: HashMap::new(), // TODO: Sidekick Entity Extraction
             synapses: Vec::new(),       // TODO: Regex Link Extraction
             chunks,
+            captured_at: None,
+            thumbnail: None,
         };
 
-        self.library.insert(path, engram);
-        Ok(())
+        Ok(engram)
+    }
+
+    // --- PIPELINE A2: VISUAL ENGRAMS (The Witness) ---
+
+    /// Build a visual engram for what was on screen at `captured_at` (Unix seconds).
+    /// The description (the OCR text) is the only text; it is redacted like any file.
+    pub fn prepare_visual(&self, description: &str, thumbnail: Option<Vec<u8>>, captured_at: u64, embedder: &impl Embedder) -> Result<Engram, String> {
+        let when = chrono::DateTime::from_timestamp(captured_at as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let (clean, _) = self.privacy.redact(description);
+        let text = format!("[Screen @ {}] {}", when, clean);
+
+        let chunk = Chunk {
+            id: 0,
+            start_byte: 0,
+            end_byte: text.len(),
+            embedding: embedder.embed(&text),
+            access_count: 0,
+        };
+        let skeleton_compressed = zstd::encode_all(Cursor::new(text.as_bytes()), 3)
+            .map_err(|e| format!("Compression failed: {}", e))?;
+
+        Ok(Engram {
+            path: PathBuf::from(format!("witness://{}", captured_at)),
+            file_type: FileType::Visual,
+            hash: String::new(),
+            skeleton_compressed,
+            entity_map: HashMap::new(),
+            synapses: Vec::new(),
+            chunks: vec![chunk],
+            captured_at: Some(captured_at),
+            thumbnail,
+        })
+    }
+
+    // --- COMMIT (The only write path for new engrams) ---
+
+    /// Insert a prepared engram. Visual engrams beyond VISUAL_ENGRAM_LIMIT are evicted oldest-first.
+    pub fn commit(&mut self, engram: Engram) {
+        let is_visual = engram.captured_at.is_some();
+        self.insert_engram(engram);
+        if !is_visual { return; }
+
+        let mut visual: Vec<(u64, PathBuf)> = self.library.values()
+            .filter_map(|e| e.captured_at.map(|t| (t, e.path.clone())))
+            .collect();
+        if visual.len() > config::VISUAL_ENGRAM_LIMIT {
+            visual.sort();
+            let excess = visual.len() - config::VISUAL_ENGRAM_LIMIT;
+            for (_, path) in visual.into_iter().take(excess) {
+                self.forget(&path);
+            }
+        }
     }
 
     // --- PIPELINE B: RETRIEVAL (Biomimetic) ---
 
     pub fn retrieve_context(&mut self, query_text: &str, query_vec: &[f32]) -> Vec<String> {
+        let hits = self.recall(query_text, query_vec);
+
+        // Reinforce these memories (LTP) so they are easier to find next time
+        for hit in &hits { self.reinforce(&hit.path, hit.chunk_id); }
+
+        hits.into_iter().map(|hit| hit.text).collect()
+    }
+
+    /// The read-only core of `retrieve_context`: ranked hits (best first), no LTP side effects.
+    pub fn recall(&self, query_text: &str, query_vec: &[f32]) -> Vec<Recollection> {
+        let params = &self.params;
+
         // Map of (Path, ChunkID) -> Activation Score
         let mut activations: HashMap<(PathBuf, usize), f32> = HashMap::new();
 
@@ -150,7 +420,7 @@ impl MemorySystem {
                 if query_text.contains(entity) {
                     for id in ids {
                         // High base score for exact keyword matches
-                        activations.insert((path.clone(), *id), 1.5);
+                        activations.insert((path.clone(), *id), params.entity_score);
                     }
                 }
             }
@@ -165,12 +435,12 @@ impl MemorySystem {
             let mut boost = 1.0;
             if let Some(engram) = self.library.get(path) {
                 if let Some(chunk) = engram.chunks.get(*chunk_idx) {
-                    boost += chunk.access_count as f32 * 0.01;
+                    boost += chunk.access_count as f32 * params.ltp_rate;
                 }
             }
             
             let score = similarity * boost;
-            if score > 0.7 {
+            if score > params.vector_threshold {
                  let entry = activations.entry((path.clone(), *chunk_idx)).or_insert(0.0);
                  *entry = entry.max(score);
             }
@@ -180,11 +450,11 @@ impl MemorySystem {
         let mut synaptic_boosts: Vec<((PathBuf, usize), f32)> = Vec::new();
 
         for ((path, _), score) in &activations {
-            if *score > 0.8 { // Only strong signals propagate
+            if *score > params.propagation_threshold { // Only strong signals propagate
                 if let Some(engram) = self.library.get(path) {
                     for synapse in &engram.synapses {
                         // Formula: Origin * Strength * Decay
-                        let transmission = score * synapse.strength * 0.5;
+                        let transmission = score * synapse.strength * params.synaptic_decay;
                         
                         // Activate Chunk 0 (Summary) of target file
                         synaptic_boosts.push(((synapse.target.clone(), 0), transmission));
@@ -201,31 +471,33 @@ impl MemorySystem {
 
         // PHASE 3: ARCHIPELAGO ASSEMBLY (Merging)
         // 1. Filter Top Hits
-        let top_hits: HashSet<(PathBuf, usize)> = activations.into_iter()
-            .filter(|(_, score)| *score > 0.75)
-            .map(|(key, _)| key)
+        let top_hits: HashMap<(PathBuf, usize), f32> = activations.into_iter()
+            .filter(|(_, score)| *score > params.selection_threshold)
             .collect();
             
         // 2. Group by File
-        let mut file_hits: HashMap<PathBuf, Vec<usize>> = HashMap::new();
-        for (path, idx) in top_hits {
-            file_hits.entry(path).or_default().push(idx);
+        let mut file_hits: HashMap<PathBuf, Vec<(usize, f32)>> = HashMap::new();
+        for ((path, idx), score) in top_hits {
+            file_hits.entry(path).or_default().push((idx, score));
         }
 
         // 3. Expand & Read (The Context Window)
-        let mut context_blocks = Vec::new();
+        let mut recollections = Vec::new();
         for (path, hits) in file_hits {
-             // Reinforce these memories (LTP) so they are easier to find next time
-             for &h in &hits { self.reinforce(&path, h); }
-             
              // In a real implementation, we would merge adjacent chunks (Islands) here.
              // For now, we grab the raw chunks.
-             if let Ok(content) = fs::read_to_string(&path) {
-                 if let Some(engram) = self.library.get(&path) {
-                     for idx in hits {
+             if let Some(engram) = self.library.get(&path) {
+                 if let Some(content) = engram_text(engram) {
+                     for (idx, score) in hits {
                          if let Some(chunk) = engram.chunks.get(idx) {
-                             if chunk.end_byte <= content.len() {
-                                 context_blocks.push(content[chunk.start_byte..chunk.end_byte].to_string());
+                             if let Some(raw) = content.get(chunk.start_byte..chunk.end_byte) {
+                                 // The file may have changed since indexing; redact again on the way out
+                                 recollections.push(Recollection {
+                                     path: path.clone(),
+                                     chunk_id: idx,
+                                     score,
+                                     text: self.privacy.redact(raw).0,
+                                 });
                              }
                          }
                      }
@@ -233,16 +505,46 @@ impl MemorySystem {
              }
         }
 
-        context_blocks
+        // 4. Rank (ties broken by location so results are deterministic)
+        recollections.sort_by(|a, b| {
+            b.score.total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.chunk_id.cmp(&b.chunk_id))
+        });
+        recollections
+    }
+
+    pub fn params(&self) -> &RetrievalParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: RetrievalParams) {
+        self.params = params;
     }
 
     // --- UTILS ---
 
+    /// Remove an engram and its fast-index entries.
+    pub fn forget(&mut self, path: &Path) {
+        if self.library.remove(path).is_some() {
+            self.flat_index.retain(|(p, _, _)| p != path);
+            self.revision += 1;
+        }
+    }
+
+    /// Apply a batch of LTP reinforcements collected from concurrent recalls.
+    pub fn reinforce_batch(&mut self, hits: &[(PathBuf, usize)]) {
+        for (path, chunk_id) in hits {
+            self.reinforce(path, *chunk_id);
+        }
+    }
+
     /// Manually strengthen a memory path (LTP)
     pub fn reinforce(&mut self, path: &PathBuf, chunk_id: usize) {
         if let Some(engram) = self.library.get_mut(path) {
             if let Some(chunk) = engram.chunks.get_mut(chunk_id) {
                 chunk.access_count += 1;
+                self.revision += 1;
             }
         }
     }
@@ -251,6 +553,7 @@ impl MemorySystem {
     pub fn forge_synapse(&mut self, source: PathBuf, target: PathBuf, strength: f32) {
         if let Some(engram) = self.library.get_mut(&source) {
             engram.synapses.push(Synapse { target, strength });
+            self.revision += 1;
         }
     }
 
@@ -285,10 +588,182 @@ impl MemorySystem {
     }
 }
 
+/// Write-then-rename so a crash never leaves a half-written file.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Source text the chunk offsets refer to: the file on disk, or the stored
+/// description for visual engrams (which have no backing file).
+fn engram_text(engram: &Engram) -> Option<String> {
+    match engram.file_type {
+        FileType::Visual => zstd::decode_all(Cursor::new(&engram.skeleton_compressed)).ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok()),
+        _ => fs::read_to_string(&engram.path).ok(),
+    }
+}
+
 // Simple Cosine Similarity Helper
 fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
     let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
     let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
     if norm_a == 0.0 || norm_b == 0.0 { 0.0 } else { dot / (norm_a * norm_b) }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::HashEmbedder;
+    use crate::vault::KeySource;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cartesian-hippocampus-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn memory_with(dir: &Path, files: &[(&str, &str)]) -> MemorySystem {
+        let embedder = HashEmbedder::new();
+        let mut memory = MemorySystem::new();
+        for (name, text) in files {
+            let path = dir.join(name);
+            fs::write(&path, text).unwrap();
+            memory.index_file(path, &embedder).unwrap();
+        }
+        memory
+    }
+
+    #[test]
+    fn visual_engrams_are_redacted_and_evicted_oldest_first() {
+        let embedder = HashEmbedder::new();
+        let mut memory = MemorySystem::new();
+        let engram = memory.prepare_visual(
+            "Text on screen: export API_KEY=sk_live_0123456789abcdef failed",
+            Some(vec![0x89, b'P', b'N', b'G']),
+            1_700_000_000,
+            &embedder,
+        ).unwrap();
+
+        assert_eq!(engram.path, PathBuf::from("witness://1700000000"));
+        assert_eq!(engram.file_type, FileType::Visual);
+        assert_eq!(engram.captured_at, Some(1_700_000_000));
+        assert!(engram.thumbnail.is_some());
+        let text = String::from_utf8(zstd::decode_all(Cursor::new(&engram.skeleton_compressed)).unwrap()).unwrap();
+        assert!(text.starts_with("[Screen @ "), "{}", text);
+        assert!(text.contains("[REDACTED:") && !text.contains("0123456789abcdef"), "{}", text);
+        assert_eq!(engram.chunks[0].embedding, embedder.embed(&text));
+
+        for t in 0..config::VISUAL_ENGRAM_LIMIT as u64 + 1 {
+            memory.commit(memory.prepare_visual("Text on screen: build ok", None, t, &embedder).unwrap());
+        }
+        assert_eq!(memory.engram_count(), config::VISUAL_ENGRAM_LIMIT);
+        assert!(!memory.library.contains_key(Path::new("witness://0")));
+        assert!(memory.library.contains_key(Path::new("witness://500")));
+    }
+
+    #[test]
+    fn sealed_store_is_never_overwritten_with_plaintext() {
+        let dir = scratch("sealed");
+        let memory = memory_with(&dir, &[("notes.md", "The rocket engine test stand needs a new pressure valve before Friday.")]);
+        let store = dir.join("hippocampus.bin");
+        let vault = Vault::new(KeySource::Passphrase("correct horse".to_string()));
+
+        memory.save(&store, Some(&vault)).unwrap();
+        let sealed = fs::read(&store).unwrap();
+        assert!(Vault::is_sealed(&sealed));
+
+        assert!(MemorySystem::load(&store, None).is_err());
+        assert!(MemorySystem::new().save(&store, None).is_err());
+        assert_eq!(fs::read(&store).unwrap(), sealed);
+        assert_eq!(MemorySystem::load(&store, Some(&vault)).unwrap().engram_count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    const ALPHA: &str = "Alpha notes: the telescope mount needs recalibration after every firmware update.";
+    const BETA: &str = "Beta notes: the greenhouse irrigation timer runs at six every morning in summer.";
+
+    #[test]
+    fn archive_round_trip_is_sealed_with_the_vault() {
+        let dir = scratch("archive");
+        let mut memory = memory_with(&dir, &[("alpha.md", ALPHA), ("beta.md", BETA)]);
+        memory.reinforce(&dir.join("alpha.md"), 0);
+        memory.forge_synapse(dir.join("alpha.md"), dir.join("beta.md"), 0.5);
+        let vault = Vault::new(KeySource::Passphrase("archive".to_string()));
+
+        let sealed = dir.join("export.cxar");
+        let manifest = memory.export_archive(&sealed, Some(&vault)).unwrap();
+        assert_eq!((manifest.engram_count, manifest.synapse_count), (2, 1));
+        assert!(Vault::is_sealed(&fs::read(&sealed).unwrap()));
+        assert!(!dir.join("export.tmp").exists());
+
+        let mut copy = MemorySystem::new();
+        assert!(copy.import_archive(&sealed, None, &[], ImportMode::Merge).is_err());
+        let report = copy.import_archive(&sealed, Some(&vault), &[], ImportMode::Merge).unwrap();
+        assert_eq!((report.added, report.merged), (2, 0));
+        let alpha = &copy.library[&dir.join("alpha.md")];
+        assert_eq!(alpha.chunks[0].access_count, memory.library[&dir.join("alpha.md")].chunks[0].access_count);
+        assert_eq!(alpha.synapses[0].target, dir.join("beta.md"));
+
+        // Plaintext on request, readable without a key
+        let plain = dir.join("plain.cxar");
+        memory.export_archive(&plain, None).unwrap();
+        assert!(archive::unpack(&fs::read(&plain).unwrap()).is_ok());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn import_remaps_paths_and_synapses() {
+        let dir = scratch("remap");
+        let mut memory = memory_with(&dir, &[("alpha.md", ALPHA), ("beta.md", BETA)]);
+        memory.forge_synapse(dir.join("alpha.md"), dir.join("beta.md"), 0.5);
+        let file = dir.join("export.cxar");
+        memory.export_archive(&file, None).unwrap();
+
+        let moved = PathBuf::from("/srv/elsewhere");
+        let remaps = [PathRemap { from: dir.clone(), to: moved.clone() }];
+        let mut copy = MemorySystem::new();
+        copy.import_archive(&file, None, &remaps, ImportMode::Merge).unwrap();
+
+        let alpha = &copy.library[&moved.join("alpha.md")];
+        assert_eq!(alpha.synapses[0].target, moved.join("beta.md"));
+        assert!(copy.library.keys().all(|p| p.starts_with(&moved)));
+        assert!(copy.flat_index.iter().all(|(p, _, _)| p.starts_with(&moved)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn merge_keeps_local_engrams_and_replace_drops_them() {
+        let dir = scratch("merge");
+        let mut exported = memory_with(&dir, &[("alpha.md", ALPHA)]);
+        for _ in 0..3 {
+            exported.reinforce(&dir.join("alpha.md"), 0);
+        }
+        let file = dir.join("export.cxar");
+        exported.export_archive(&file, None).unwrap();
+
+        let mut local = memory_with(&dir, &[("alpha.md", ALPHA), ("beta.md", BETA)]);
+        local.reinforce(&dir.join("alpha.md"), 0);
+        let report = local.import_archive(&file, None, &[], ImportMode::Merge).unwrap();
+        assert_eq!((report.added, report.merged), (0, 1));
+        assert_eq!(local.engram_count(), 2);
+        // LTP counters take the larger of both
+        assert_eq!(local.library[&dir.join("alpha.md")].chunks[0].access_count, exported.library[&dir.join("alpha.md")].chunks[0].access_count);
+
+        let report = local.import_archive(&file, None, &[], ImportMode::Replace).unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(local.engram_count(), 1);
+        assert!(!local.library.contains_key(&dir.join("beta.md")));
+        assert!(local.flat_index.iter().all(|(p, _, _)| p == &dir.join("alpha.md")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
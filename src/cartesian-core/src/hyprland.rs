@@ -0,0 +1,245 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use serde::Deserialize;
+
+/// The window the user is looking at, as Hyprland reports it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusedWindow {
+    pub class: String,
+    pub title: String,
+    pub pid: Option<u32>,
+    pub fullscreen: bool,
+    pub workspace: Option<String>,
+}
+
+/// One line from the event socket (`EVENT>>DATA`). Only the events that
+/// change what we track are told apart.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    ActiveWindow { class: String, title: String },
+    Fullscreen(bool),
+    Workspace(String),
+    WindowClosed,
+    Other,
+}
+
+pub fn parse_event(line: &str) -> Option<Event> {
+    let (name, data) = line.split_once(">>")?;
+    Some(match name {
+        "activewindow" => {
+            let (class, title) = data.split_once(',').unwrap_or((data, ""));
+            Event::ActiveWindow { class: class.to_string(), title: title.to_string() }
+        }
+        "fullscreen" => Event::Fullscreen(data.trim() == "1"),
+        "workspace" => Event::Workspace(data.to_string()),
+        "closewindow" => Event::WindowClosed,
+        _ => Event::Other,
+    })
+}
+
+/// `j/activewindow` reply. Hyprland answers `{}` when nothing is focused.
+#[derive(Debug, Deserialize)]
+struct ActiveWindowReply {
+    class: Option<String>,
+    title: Option<String>,
+    pid: Option<i64>,
+    #[serde(default)]
+    fullscreen: serde_json::Value, // bool before 0.41, a mode number since
+    workspace: Option<WorkspaceRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkspaceRef {
+    name: String,
+}
+
+impl ActiveWindowReply {
+    fn into_window(self) -> Option<FocusedWindow> {
+        let class = self.class?;
+        Some(FocusedWindow {
+            class,
+            title: self.title.unwrap_or_default(),
+            pid: self.pid.and_then(|p| u32::try_from(p).ok()),
+            fullscreen: self.fullscreen.as_bool().unwrap_or(false) || self.fullscreen.as_u64().is_some_and(|m| m > 0),
+            workspace: self.workspace.map(|w| w.name),
+        })
+    }
+}
+
+/// THE COMPOSITOR LINK
+/// Follows Hyprland's event socket (`.socket2.sock`) on a background thread.
+/// Every focus, fullscreen or workspace event triggers a `j/activewindow`
+/// query on the command socket (`.socket.sock`) for the pid and fullscreen
+/// state; the events alone are the fallback. Reconnects if Hyprland restarts.
+pub struct Hyprland {
+    focused: Arc<Mutex<Option<FocusedWindow>>>,
+}
+
+impl Hyprland {
+    /// None outside a Hyprland session.
+    pub fn connect() -> Option<Self> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+        let runtime = std::env::var("XDG_RUNTIME_DIR").ok().map(|dir| PathBuf::from(dir).join("hypr").join(&signature));
+        // Before 0.40 the sockets lived under /tmp
+        let dir = runtime.filter(|dir| dir.exists()).unwrap_or_else(|| PathBuf::from("/tmp/hypr").join(&signature));
+        dir.exists().then(|| Self::at(dir))
+    }
+
+    /// Follow the sockets in `dir`.
+    pub fn at(dir: PathBuf) -> Self {
+        let focused = Arc::new(Mutex::new(None));
+        let shared = focused.clone();
+        std::thread::spawn(move || follow(dir, shared));
+        Self { focused }
+    }
+
+    pub fn focused(&self) -> Option<FocusedWindow> {
+        self.focused.lock().ok()?.clone()
+    }
+}
+
+#[cfg(unix)]
+fn follow(dir: PathBuf, focused: Arc<Mutex<Option<FocusedWindow>>>) {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+
+    let update = |window: Option<FocusedWindow>| {
+        if let Ok(mut focused) = focused.lock() { *focused = window; }
+    };
+
+    let mut warned = false;
+    loop {
+        let events = match UnixStream::connect(dir.join(".socket2.sock")) {
+            Ok(stream) => stream,
+            Err(e) => {
+                if !warned {
+                    eprintln!("Hyprland: Event socket unavailable: {}", e);
+                    warned = true;
+                }
+                std::thread::sleep(crate::config::HYPRLAND_RECONNECT_DELAY);
+                continue;
+            }
+        };
+        warned = false;
+        update(query_active(&dir));
+
+        for line in BufReader::new(events).lines() {
+            let Ok(line) = line else { break; };
+            let Some(event) = parse_event(&line) else { continue; };
+            if event == Event::Other { continue; }
+
+            // The query is authoritative; the event data only fills in if it fails
+            let window = query_active(&dir).or_else(|| {
+                let mut window = focused.lock().ok()?.clone().unwrap_or_default();
+                match event {
+                    Event::ActiveWindow { class, .. } if class.is_empty() => return None,
+                    Event::ActiveWindow { class, title } => {
+                        window = FocusedWindow { class, title, workspace: window.workspace, ..Default::default() };
+                    }
+                    Event::Fullscreen(on) => window.fullscreen = on,
+                    Event::Workspace(name) => window.workspace = Some(name),
+                    Event::WindowClosed | Event::Other => {}
+                }
+                Some(window)
+            });
+            update(window);
+        }
+
+        eprintln!("Hyprland: Event socket closed, reconnecting.");
+        update(None);
+        std::thread::sleep(crate::config::HYPRLAND_RECONNECT_DELAY);
+    }
+}
+
+#[cfg(not(unix))]
+fn follow(_dir: PathBuf, _focused: Arc<Mutex<Option<FocusedWindow>>>) {}
+
+/// One request on the command socket; Hyprland closes it after replying.
+#[cfg(unix)]
+fn query_active(dir: &std::path::Path) -> Option<FocusedWindow> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(dir.join(".socket.sock")).ok()?;
+    stream.set_read_timeout(Some(crate::config::HYPRLAND_QUERY_TIMEOUT)).ok()?;
+    stream.write_all(b"j/activewindow").ok()?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).ok()?;
+    serde_json::from_str::<ActiveWindowReply>(&reply).ok()?.into_window()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn events_parse() {
+        assert_eq!(
+            parse_event("activewindow>>steam_app_1245620,ELDEN RING™, the game"),
+            Some(Event::ActiveWindow { class: "steam_app_1245620".into(), title: "ELDEN RING™, the game".into() })
+        );
+        assert_eq!(parse_event("fullscreen>>1"), Some(Event::Fullscreen(true)));
+        assert_eq!(parse_event("workspace>>3"), Some(Event::Workspace("3".into())));
+        assert_eq!(parse_event("openlayer>>waybar"), Some(Event::Other));
+        assert_eq!(parse_event("garbage"), None);
+    }
+
+    fn wait_for(hypr: &Hyprland, check: impl Fn(&Option<FocusedWindow>) -> bool) -> Option<FocusedWindow> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let focused = hypr.focused();
+            if check(&focused) || Instant::now() > deadline {
+                return focused;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// A fake Hyprland: the command socket answers from `replies` in turn,
+    /// the event socket sends whatever the test pushes.
+    #[test]
+    fn follows_a_fake_compositor() {
+        let dir = std::env::temp_dir().join(format!("cartesian-hypr-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let commands = UnixListener::bind(dir.join(".socket.sock")).unwrap();
+        let events = UnixListener::bind(dir.join(".socket2.sock")).unwrap();
+
+        let replies = Arc::new(Mutex::new(vec![
+            "{}".to_string(),
+            r#"{"class": "code", "title": "main.rs", "pid": 4242, "fullscreen": 0, "workspace": {"id": 2, "name": "2"}}"#.to_string(),
+            r#"{"class": "steam_app_620", "title": "Portal 2", "pid": 5151, "fullscreen": 2, "workspace": {"id": 5, "name": "5"}}"#.to_string(),
+        ]));
+        std::thread::spawn(move || {
+            for stream in commands.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 64];
+                let n = stream.read(&mut request).unwrap();
+                assert_eq!(&request[..n], b"j/activewindow");
+                let mut replies = replies.lock().unwrap();
+                let reply = if replies.len() > 1 { replies.remove(0) } else { replies[0].clone() };
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+
+        let hypr = Hyprland::at(dir.clone());
+        let (mut stream, _) = events.accept().unwrap();
+        assert_eq!(hypr.focused(), None, "nothing focused at start");
+
+        stream.write_all(b"activewindow>>code,main.rs\n").unwrap();
+        let window = wait_for(&hypr, |w| w.is_some()).unwrap();
+        assert_eq!((window.pid, window.fullscreen, window.workspace.as_deref()), (Some(4242), false, Some("2")));
+
+        stream.write_all(b"openlayer>>waybar\nworkspace>>5\nfullscreen>>1\n").unwrap();
+        let window = wait_for(&hypr, |w| w.as_ref().is_some_and(|w| w.fullscreen)).unwrap();
+        assert_eq!((window.class.as_str(), window.pid), ("steam_app_620", Some(5151)));
+
+        // Compositor gone: focus is unknown again
+        drop(events);
+        drop(stream);
+        assert_eq!(wait_for(&hypr, |w| w.is_none()), None);
+    }
+}
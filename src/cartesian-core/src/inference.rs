@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
 use std::path::Path;
-use std::time::Instant;
-// REMOVED: unused imports (Arc, Mutex)
+use std::sync::{Arc, RwLock};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
 use anyhow::{Result, Error as E}; // REMOVED: unused Context
 
 // CANDLE IMPORTS
@@ -12,11 +15,16 @@ use candle_transformers::models::quantized_llama as model;
 use model::ModelWeights;
 use tokenizers::Tokenizer;
 
+use serde::{Serialize, Deserialize};
+
 use crate::config;
+use crate::policy::{GovernorInputs, GovernorPolicy, Schedule};
+use crate::accounting::{self, Resident, Subsystem};
+use crate::cgroup::{self, Group};
 use crate::schema::ActionSchema;
 use image::DynamicImage;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum GovernorState {
     GodMode,
     Conscientious,
@@ -24,9 +32,202 @@ pub enum GovernorState {
     PotatoMode,
 }
 
+impl GovernorState {
+    /// Resource appetite: higher ranks hold more VRAM/RAM.
+    pub fn rank(&self) -> u8 {
+        match self {
+            GovernorState::PotatoMode => 0,
+            GovernorState::SidekickMode => 1,
+            GovernorState::Conscientious => 2,
+            GovernorState::GodMode => 3,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GovernorState::GodMode => "GOD MODE",
+            GovernorState::Conscientious => "CONSCIENTIOUS",
+            GovernorState::SidekickMode => "SIDEKICK",
+            GovernorState::PotatoMode => "POTATO",
+        }
+    }
+}
+
+/// Dwell time for a specific transition. `from: None` matches any origin
+/// `to` is a downgrade from; climbing back up always takes the usual dwell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DwellRule {
+    #[serde(default)]
+    pub from: Option<GovernorState>,
+    pub to: GovernorState,
+    pub secs: f32,
+}
+
+/// How long a new target must be requested continuously before the Governor commits to it.
+/// Asymmetric by design: giving resources back is fast, taking them needs sustained headroom.
+/// Leaving Potato counts as taking, so free RAM hovering at the threshold doesn't flap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hysteresis {
+    pub upgrade_secs: f32,
+    pub downgrade_secs: f32,
+    pub rules: Vec<DwellRule>, // First match wins
+}
+
+impl Default for Hysteresis {
+    fn default() -> Self {
+        let emergency = config::GOVERNOR_EMERGENCY_DWELL.as_secs_f32();
+        Self {
+            upgrade_secs: config::GOVERNOR_UPGRADE_DWELL.as_secs_f32(),
+            downgrade_secs: config::GOVERNOR_DOWNGRADE_DWELL.as_secs_f32(),
+            // EMERGENCY FAST-PATH: game launch / RAM exhaustion must free the GPU now
+            rules: vec![
+                DwellRule { from: None, to: GovernorState::PotatoMode, secs: emergency },
+                DwellRule { from: None, to: GovernorState::SidekickMode, secs: emergency },
+            ],
+        }
+    }
+}
+
+impl Hysteresis {
+    pub fn dwell(&self, from: GovernorState, to: GovernorState) -> Duration {
+        let is_downgrade = to.rank() < from.rank();
+        let secs = self.rules.iter()
+            .find(|r| r.to == to && r.from.map_or(is_downgrade, |f| f == from))
+            .map(|r| r.secs)
+            .unwrap_or(if is_downgrade { self.downgrade_secs } else { self.upgrade_secs });
+        Duration::from_secs_f32(secs.max(0.0))
+    }
+}
+
+/// How much room background jobs (Hippocampus indexing, Witness sampling) get.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundBudget {
+    Full,
+    Throttled,
+    Paused,
+}
+
+/// Background work that can be budgeted on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundJob {
+    Consolidation, // Hippocampus indexing
+    Witness,       // Visual engram sampling
+}
+
+/// Where a profile's model lives. candle's quantized_llama keeps every layer
+/// on one device, so there is no split between the two.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    #[default]
+    Gpu, // Falls back to the CPU without CUDA
+    Cpu,
+}
+
+/// What a GovernorState means for the Engine. Model or placement changes
+/// reload the weights; context and batch size apply from the next prompt.
+/// The CPU and memory limits take effect through cgroup confinement, if available.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EngineProfile {
+    pub model: Option<String>, // None = unloaded
+    #[serde(default)]
+    pub placement: Placement,
+    pub context_length: usize,
+    pub batch_size: usize,
+    pub background: BackgroundBudget,
+    #[serde(default = "default_cpu_weight")]
+    pub cpu_weight: u32, // Inference threads, 1-10000
+    #[serde(default)]
+    pub cpu_max: Option<f32>, // Inference threads, in CPUs; None = unlimited
+    #[serde(default)]
+    pub memory_high_gb: Option<f32>, // Whole process; None = unlimited
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub jobs: BTreeMap<BackgroundJob, BackgroundBudget>, // Per-job overrides of `background`
+}
+
+fn default_cpu_weight() -> u32 { 100 }
+
+impl EngineProfile {
+    pub fn budget(&self, job: BackgroundJob) -> BackgroundBudget {
+        self.jobs.get(&job).copied().unwrap_or(self.background)
+    }
+
+    pub fn builtin(state: GovernorState) -> Self {
+        match state {
+            GovernorState::GodMode => Self {
+                model: Some(config::MODEL_GOD.to_string()),
+                placement: Placement::Gpu,
+                context_length: 4096,
+                batch_size: 512,
+                background: BackgroundBudget::Full,
+                cpu_weight: 100,
+                cpu_max: None,
+                memory_high_gb: None,
+                jobs: BTreeMap::new(),
+            },
+            // The Manager, still on the GPU, with a smaller KV cache and
+            // activations and throttled background jobs
+            GovernorState::Conscientious => Self {
+                model: Some(config::MODEL_GOD.to_string()),
+                placement: Placement::Gpu,
+                context_length: 2048,
+                batch_size: 128,
+                background: BackgroundBudget::Throttled,
+                cpu_weight: 50,
+                cpu_max: None,
+                memory_high_gb: None,
+                jobs: BTreeMap::new(),
+            },
+            // Off the GPU entirely
+            GovernorState::SidekickMode => Self {
+                model: Some(config::MODEL_SIDEKICK.to_string()),
+                placement: Placement::Cpu,
+                context_length: 1024,
+                batch_size: 64,
+                background: BackgroundBudget::Paused,
+                cpu_weight: 20,
+                cpu_max: Some(2.0),
+                memory_high_gb: Some(6.0),
+                jobs: BTreeMap::new(),
+            },
+            GovernorState::PotatoMode => Self {
+                model: None,
+                placement: Placement::Cpu,
+                context_length: 0,
+                batch_size: 1,
+                background: BackgroundBudget::Paused,
+                cpu_weight: 10,
+                cpu_max: Some(1.0),
+                memory_high_gb: Some(2.0),
+                jobs: BTreeMap::new(),
+            },
+        }
+    }
+}
+
+/// A manual override from the dashboard. Ends on its own at `until`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hold {
+    pub state: GovernorState,
+    pub until: Instant,
+}
+
 pub struct Governor {
     current_state: GovernorState,
     last_state_change: Instant,
+    policy: GovernorPolicy,
+    reason: String, // Policy rule behind the latest target
+    hysteresis: Hysteresis,
+    // Target that differs from the current state, and when it was first requested
+    pending: Option<(GovernorState, Instant)>,
+    hold: Option<Hold>,
+    schedule: Option<Schedule>, // Schedule behind the latest target, if any
+    log_transitions: bool,
 }
 
 impl Governor {
@@ -34,29 +235,144 @@ impl Governor {
         Self {
             current_state: GovernorState::GodMode,
             last_state_change: Instant::now(),
+            policy: GovernorPolicy::default(),
+            reason: "default".to_string(),
+            hysteresis: Hysteresis::default(),
+            pending: None,
+            hold: None,
+            schedule: None,
+            log_transitions: true,
+        }
+    }
+
+    /// Replays run thousands of ticks; they read the outcome from the report instead.
+    pub fn set_logging(&mut self, enabled: bool) {
+        self.log_transitions = enabled;
+    }
+
+    /// Swap in a (validated) policy. Its hysteresis section, if any, replaces the current one.
+    pub fn set_policy(&mut self, policy: GovernorPolicy) {
+        self.hysteresis = policy.hysteresis.clone().unwrap_or_default();
+        self.policy = policy;
+    }
+
+    pub fn decide_state(&mut self, inputs: &GovernorInputs) -> GovernorState {
+        self.decide_state_at(inputs, Instant::now())
+    }
+
+    /// `decide_state` with an explicit clock, for replays.
+    pub fn decide_state_at(&mut self, inputs: &GovernorInputs, now: Instant) -> GovernorState {
+        if self.hold.is_some_and(|h| now >= h.until) {
+            println!("Governor: Hold expired");
+            self.hold = None;
+        }
+
+        let decision = self.policy.evaluate(inputs);
+        // Like schedules, a hold yields only to PotatoMode. It skips the dwell: the user asked for it.
+        if let Some(hold) = self.hold.filter(|_| decision.state != GovernorState::PotatoMode) {
+            self.reason = "hold".to_string();
+            self.schedule = None;
+            self.pending = None;
+            if hold.state != self.current_state {
+                return self.commit_transition(hold.state, now);
+            }
+            return self.current_state;
+        }
+
+        self.reason = decision.rule.to_string();
+        self.schedule = decision.schedule.cloned();
+        self.transition_checked(decision.state, now)
+    }
+
+    /// Pin `state` for `duration`. Holding the same state again extends the
+    /// hold, up to GOVERNOR_HOLD_MAX from now.
+    pub fn hold(&mut self, state: GovernorState, duration: Duration) {
+        self.hold_at(state, duration, Instant::now());
+    }
+
+    pub fn hold_at(&mut self, state: GovernorState, duration: Duration, now: Instant) {
+        let from = match self.hold {
+            Some(h) if h.state == state && h.until > now => h.until,
+            _ => now,
+        };
+        let until = (from + duration).min(now + config::GOVERNOR_HOLD_MAX);
+        println!("Governor: Holding {:?} for {:.0?}", state, until - now);
+        self.hold = Some(Hold { state, until });
+    }
+
+    pub fn release_hold(&mut self) {
+        if self.hold.take().is_some() {
+            println!("Governor: Hold released");
         }
     }
 
-    pub fn decide_state(&mut self, free_ram_gb: f32, is_gaming: bool, vram_pressure: bool) -> GovernorState {
-        if free_ram_gb < 2.0 { return self.transition_checked(GovernorState::PotatoMode); }
-        if is_gaming { return self.transition_checked(GovernorState::SidekickMode); }
-        if vram_pressure { return self.transition_checked(GovernorState::Conscientious); }
-        self.transition_checked(GovernorState::GodMode)
+    /// The active hold and how long it has left.
+    pub fn hold_remaining(&self) -> Option<(GovernorState, Duration)> {
+        let now = Instant::now();
+        self.hold.filter(|h| h.until > now).map(|h| (h.state, h.until - now))
+    }
+
+    /// The schedule in effect, if one decided the latest target.
+    pub fn active_schedule(&self) -> Option<&Schedule> {
+        self.schedule.as_ref()
+    }
+
+    /// Name of the policy rule that produced the latest target.
+    pub fn reason(&self) -> &str {
+        &self.reason
     }
 
-    fn transition_checked(&mut self, target: GovernorState) -> GovernorState {
-        if target == self.current_state { return self.current_state.clone(); }
-        if self.last_state_change.elapsed() > config::GOVERNOR_HYSTERESIS {
-            return self.commit_transition(target);
+    /// Engine settings for the current state, with the active schedule's exemptions.
+    pub fn profile(&self) -> EngineProfile {
+        let mut profile = self.policy.profile(self.current_state);
+        for job in self.schedule.iter().flat_map(|s| &s.exempt) {
+            profile.jobs.insert(*job, BackgroundBudget::Full);
         }
-        self.current_state.clone()
+        profile
     }
 
-    fn commit_transition(&mut self, new_state: GovernorState) -> GovernorState {
-        println!("Governor: Transition {:?} -> {:?}", self.current_state, new_state);
+    /// The profile the policy loads for `state`, whatever the current state is.
+    pub fn profile_for(&self, state: GovernorState) -> EngineProfile {
+        self.policy.profile(state)
+    }
+
+    /// A target waiting out its dwell time, if any.
+    pub fn pending_target(&self) -> Option<GovernorState> {
+        self.pending.map(|(target, _)| target)
+    }
+
+    fn transition_checked(&mut self, target: GovernorState, now: Instant) -> GovernorState {
+        if target == self.current_state {
+            self.pending = None;
+            return self.current_state;
+        }
+
+        // The target must be requested continuously; a change of mind restarts the clock
+        let since = match self.pending {
+            Some((pending, since)) if pending == target => since,
+            _ => {
+                self.pending = Some((target, now));
+                now
+            }
+        };
+
+        if now.saturating_duration_since(since) >= self.hysteresis.dwell(self.current_state, target) {
+            return self.commit_transition(target, now);
+        }
+        self.current_state
+    }
+
+    fn commit_transition(&mut self, new_state: GovernorState, now: Instant) -> GovernorState {
+        if self.log_transitions {
+            println!(
+                "Governor: Transition {:?} -> {:?} (after {:.0?} in state)",
+                self.current_state, new_state, now.saturating_duration_since(self.last_state_change)
+            );
+        }
         self.current_state = new_state;
-        self.last_state_change = Instant::now();
-        self.current_state.clone()
+        self.last_state_change = now;
+        self.pending = None;
+        self.current_state
     }
 }
 
@@ -65,8 +381,10 @@ impl Governor {
 pub struct Engine {
     model: Option<ModelWeights>,
     tokenizer: Option<Tokenizer>,
-    device: Device,
+    gpu: Option<Device>, // CUDA, if present
+    device: Device,      // Where the loaded model lives
     current_model_name: String,
+    profile: Option<EngineProfile>,
     // Cache for GGUF handling
     logits_processor: LogitsProcessor,
 }
@@ -74,39 +392,42 @@ pub struct Engine {
 impl Engine {
     pub fn new() -> Self {
         // Auto-detect CUDA. If fail, fall back to CPU.
-        let device = Device::new_cuda(0).unwrap_or(Device::Cpu);
-        println!("Engine: Initialized on Device: {:?}", device);
+        let gpu = Device::new_cuda(0).ok();
+        println!("Engine: Initialized on Device: {:?}", gpu.as_ref().unwrap_or(&Device::Cpu));
 
         Self {
             model: None,
             tokenizer: None,
-            device,
+            gpu,
+            device: Device::Cpu,
             current_model_name: String::new(),
+            profile: None,
             logits_processor: LogitsProcessor::new(42, Some(0.9), Some(1.1)), // Seed, Temp, Top-P
         }
     }
 
-    pub fn apply_state(&mut self, state: &GovernorState) -> bool {
-        let target_model = match state {
-            GovernorState::GodMode => config::MODEL_GOD,
-            GovernorState::Conscientious => config::MODEL_SIDEKICK,
-            GovernorState::SidekickMode => config::MODEL_SIDEKICK,
-            GovernorState::PotatoMode => {
-                self.unload();
-                "None"
-            }
-        };
-
-        if self.current_model_name == target_model {
+    /// Returns true if the profile changed.
+    pub fn apply_profile(&mut self, profile: &EngineProfile) -> bool {
+        if self.profile.as_ref() == Some(profile) {
             return false;
         }
 
-        if target_model != "None" {
-            if let Err(e) = self.load_model(target_model) {
-                eprintln!("Engine Error: Failed to load {}: {}", target_model, e);
-                return false;
+        match &profile.model {
+            None => self.unload(),
+            Some(model_name) => {
+                let needs_reload = self.model.is_none() || self.profile.as_ref()
+                    .is_none_or(|p| p.model != profile.model || p.placement != profile.placement);
+                if needs_reload {
+                    let _span = accounting::enter(Subsystem::Inference);
+                    if let Err(e) = self.load_model(model_name, profile.placement) {
+                        eprintln!("Engine Error: Failed to load {}: {}", model_name, e);
+                        return false;
+                    }
+                }
             }
         }
+
+        self.profile = Some(profile.clone());
         true
     }
 
@@ -117,7 +438,14 @@ impl Engine {
         println!("Engine: Brain unloaded.");
     }
 
-    fn load_model(&mut self, model_name: &str) -> Result<()> {
+    fn device(&self, placement: Placement) -> Device {
+        match (placement, &self.gpu) {
+            (Placement::Gpu, Some(gpu)) => gpu.clone(),
+            _ => Device::Cpu,
+        }
+    }
+
+    fn load_model(&mut self, model_name: &str, placement: Placement) -> Result<()> {
         let base_dir = config::get_model_dir();
         let model_path = Path::new(&base_dir).join(model_name);
         
@@ -129,10 +457,11 @@ impl Engine {
         let mut file = std::fs::File::open(&model_path)?;
         // FIX: Parse content first using the candle_core::quantized::gguf_file module
         let content = gguf_file::Content::read(&mut file)?;
+        let device = self.device(placement);
         
         // 2. Load Weights using the content
         // FIX: Pass content as the first argument
-        let model = model::ModelWeights::from_gguf(content, &mut file, &self.device)?;
+        let model = model::ModelWeights::from_gguf(content, &mut file, &device)?;
         
         // 3. Load Tokenizer
         if !tokenizer_path.exists() {
@@ -142,6 +471,7 @@ impl Engine {
 
         self.model = Some(model);
         self.tokenizer = Some(tokenizer);
+        self.device = device;
         self.current_model_name = model_name.to_string();
         
         println!("Engine: Brain Loaded Successfully.");
@@ -149,6 +479,7 @@ impl Engine {
     }
     
     pub fn infer_action(&mut self, prompt: &str, _image: Option<&DynamicImage>) -> Option<ActionSchema> {
+        let _span = accounting::enter(Subsystem::Inference);
         let model = self.model.as_mut()?;
         let tokenizer = self.tokenizer.as_ref()?;
 
@@ -156,12 +487,23 @@ impl Engine {
         let formatted_prompt = format!("<start_of_turn>user\n{}<end_of_turn>\n<start_of_turn>model\n", prompt);
         
         let tokens = tokenizer.encode(formatted_prompt, true).ok()?;
-        let prompt_tokens = tokens.get_ids();
+        let (context_length, batch_size) = self.profile.as_ref()
+            .map(|p| (p.context_length, p.batch_size))
+            .unwrap_or((model::MAX_SEQ_LEN, 512));
+        // Keep the most recent tokens when the prompt outgrows the context
+        let context_length = context_length.clamp(1, model::MAX_SEQ_LEN);
+        let all_tokens = tokens.get_ids();
+        let prompt_tokens = &all_tokens[all_tokens.len().saturating_sub(context_length)..];
         
         // --- INFERENCE LOOP ---
-        let input = Tensor::new(prompt_tokens, &self.device).ok()?.unsqueeze(0).ok()?;
-        let logits = model.forward(&input, 0).ok()?;
-        let _logits = logits.squeeze(0).ok()?; // FIX: Renamed to _logits to silence unused warning
+        // Prefill in batches; the KV cache carries state between them
+        let batch_size = batch_size.max(1);
+        let mut logits = None;
+        for (i, batch) in prompt_tokens.chunks(batch_size).enumerate() {
+            let input = Tensor::new(batch, &self.device).ok()?.unsqueeze(0).ok()?;
+            logits = Some(model.forward(&input, i * batch_size).ok()?);
+        }
+        let _logits = logits?.squeeze(0).ok()?; // FIX: Renamed to _logits to silence unused warning
 
         // Sample logic stub (Commented out in original, kept commented)
         // let next_token = self.logits_processor.sample(&logits).ok()?;
@@ -175,23 +517,255 @@ impl Engine {
             status: crate::schema::TaskStatus::Active,
         })
     }
-    
+
+
     pub fn current_model(&self) -> String {
         self.current_model_name.clone()
     }
+
+    /// Memory held by the loaded model: VRAM on the GPU, RAM on the CPU.
+    pub fn resident(&self) -> Resident {
+        if self.model.is_none() {
+            return Resident::default();
+        }
+        let gb = crate::vram::model_footprint_gb(&self.current_model_name).unwrap_or(0.0);
+        if self.device.is_cuda() {
+            Resident { ram_gb: 0.0, vram_gb: gb }
+        } else {
+            Resident { ram_gb: gb, vram_gb: 0.0 }
+        }
+    }
+}
+
+// --- THE ENGINE THREAD ---
+
+enum EngineJob {
+    Profile(EngineProfile),
+    Infer(String, Option<DynamicImage>, Sender<Option<ActionSchema>>),
+}
+
+/// What the dashboard and the Governor read without waiting on the Engine.
+#[derive(Debug, Clone, Default)]
+struct EngineStatus {
+    model: String,
+    resident: Resident,
+}
+
+/// THE ENGINE THREAD
+/// The Engine on a dedicated thread in the inference cgroup, so the CPU limits
+/// of Sidekick and Potato hold back the model and not the dashboard. Jobs run
+/// in order; answers come back on their own channels.
+#[derive(Clone)]
+pub struct EngineHandle {
+    jobs: Sender<EngineJob>,
+    status: Arc<RwLock<EngineStatus>>,
+}
+
+impl EngineHandle {
+    /// Call after `cgroup::init`, so the thread lands in the layout.
+    pub fn spawn() -> Self {
+        let (jobs, rx) = mpsc::channel();
+        let status = Arc::new(RwLock::new(EngineStatus::default()));
+        let shared = status.clone();
+
+        thread::Builder::new()
+            .name("cartesian-engine".to_string())
+            .spawn(move || {
+                cgroup::join(Group::Inference);
+                let mut engine = Engine::new();
+                for job in rx {
+                    match job {
+                        EngineJob::Profile(profile) => {
+                            engine.apply_profile(&profile);
+                        }
+                        EngineJob::Infer(prompt, image, reply) => {
+                            let _ = reply.send(engine.infer_action(&prompt, image.as_ref()));
+                        }
+                    }
+                    if let Ok(mut status) = shared.write() {
+                        *status = EngineStatus { model: engine.current_model(), resident: engine.resident() };
+                    }
+                }
+            })
+            .expect("Engine: Failed to spawn engine thread");
+
+        Self { jobs, status }
+    }
+
+    /// Queue a profile; the thread ignores it if nothing changed.
+    pub fn apply_profile(&self, profile: &EngineProfile) {
+        let _ = self.jobs.send(EngineJob::Profile(profile.clone()));
+    }
+
+    /// Answer a prompt once the jobs ahead of it are done.
+    pub async fn infer_action(self, prompt: String, image: Option<DynamicImage>) -> Option<ActionSchema> {
+        let (reply, answer) = mpsc::channel();
+        self.jobs.send(EngineJob::Infer(prompt, image, reply)).ok()?;
+        tokio::task::spawn_blocking(move || answer.recv().ok().flatten())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    pub fn current_model(&self) -> String {
+        self.status.read().map(|s| s.model.clone()).unwrap_or_default()
+    }
+
+    pub fn resident(&self) -> Resident {
+        self.status.read().map(|s| s.resident).unwrap_or_default()
+    }
+}
+
+/// Anything that turns text into a vector for the Hippocampus.
+pub trait Embedder {
+    fn embed(&self, text: &str) -> Vec<f32>;
 }
 
 // Simple Embedding Engine Wrapper
 pub struct EmbeddingEngine {
     ready: bool,
+    weights_gb: f32, // MODEL_EMBEDDING, held in RAM once ready
 }
 impl EmbeddingEngine {
-    pub fn new() -> Self { Self { ready: false } }
+    pub fn new() -> Self { Self { ready: false, weights_gb: 0.0 } }
     pub fn init(&mut self) -> Result<(), String> { 
+        self.weights_gb = crate::vram::model_footprint_gb(config::MODEL_EMBEDDING).unwrap_or(0.0);
         self.ready = true; 
         Ok(()) 
     }
-    pub fn embed(&self, _text: &str) -> Vec<f32> {
+
+    /// The embedder always runs on the CPU.
+    pub fn resident(&self) -> Resident {
+        Resident { ram_gb: if self.ready { self.weights_gb } else { 0.0 }, vram_gb: 0.0 }
+    }
+}
+
+impl Embedder for EmbeddingEngine {
+    fn embed(&self, _text: &str) -> Vec<f32> {
+        let _span = accounting::enter(Subsystem::Embedding);
         vec![0.0; 384] 
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobotomy::AppCategory;
+
+    fn inputs(free_ram_gb: f32) -> GovernorInputs {
+        GovernorInputs { free_ram_gb, ..GovernorInputs::idle() }
+    }
+
+    fn gaming(free_ram_gb: f32) -> GovernorInputs {
+        GovernorInputs { active_contexts: vec![AppCategory::Game], ..inputs(free_ram_gb) }
+    }
+
+    #[test]
+    fn emergency_dwell_only_speeds_up_downgrades() {
+        let mut governor = Governor::new();
+        governor.set_logging(false);
+        let t0 = Instant::now();
+        let secs = |s: u64| t0 + Duration::from_secs(s);
+
+        // Downgrades into Sidekick and Potato are immediate
+        assert_eq!(governor.decide_state_at(&gaming(8.0), t0), GovernorState::SidekickMode);
+        assert_eq!(governor.decide_state_at(&gaming(1.9), secs(1)), GovernorState::PotatoMode);
+
+        // Potato -> Sidekick is an upgrade and waits like one
+        assert_eq!(governor.decide_state_at(&gaming(2.1), secs(2)), GovernorState::PotatoMode);
+        assert_eq!(governor.pending_target(), Some(GovernorState::SidekickMode));
+        assert_eq!(governor.decide_state_at(&gaming(2.1), secs(2) + config::GOVERNOR_UPGRADE_DWELL), GovernorState::SidekickMode);
+    }
+
+    #[test]
+    fn hovering_at_the_memory_boundary_does_not_flap() {
+        let mut governor = Governor::new();
+        governor.set_logging(false);
+        let t0 = Instant::now();
+
+        let mut states = Vec::new();
+        for s in 0..60 {
+            let free = if s % 2 == 0 { 1.9 } else { 2.1 };
+            states.push(governor.decide_state_at(&gaming(free), t0 + Duration::from_secs(s)));
+        }
+        assert!(states.iter().all(|s| *s == GovernorState::PotatoMode), "{:?}", states);
+    }
+
+    #[test]
+    fn explicit_rules_match_either_direction() {
+        let hysteresis = Hysteresis {
+            rules: vec![DwellRule { from: Some(GovernorState::PotatoMode), to: GovernorState::SidekickMode, secs: 2.0 }],
+            ..Hysteresis::default()
+        };
+        assert_eq!(hysteresis.dwell(GovernorState::PotatoMode, GovernorState::SidekickMode), Duration::from_secs(2));
+        assert_eq!(hysteresis.dwell(GovernorState::GodMode, GovernorState::SidekickMode), config::GOVERNOR_DOWNGRADE_DWELL);
+        assert_eq!(hysteresis.dwell(GovernorState::SidekickMode, GovernorState::GodMode), config::GOVERNOR_UPGRADE_DWELL);
+
+        let defaults = Hysteresis::default();
+        assert_eq!(defaults.dwell(GovernorState::GodMode, GovernorState::SidekickMode), config::GOVERNOR_EMERGENCY_DWELL);
+        assert_eq!(defaults.dwell(GovernorState::PotatoMode, GovernorState::SidekickMode), config::GOVERNOR_UPGRADE_DWELL);
+    }
+
+    #[test]
+    fn profiles_map_to_a_placement() {
+        let placement = |state| EngineProfile::builtin(state).placement;
+        assert_eq!(placement(GovernorState::GodMode), Placement::Gpu);
+        // Conscientious trims context and batch, not the device
+        assert_eq!(placement(GovernorState::Conscientious), Placement::Gpu);
+        assert_eq!(placement(GovernorState::SidekickMode), Placement::Cpu);
+        assert_eq!(placement(GovernorState::PotatoMode), Placement::Cpu);
+
+        let god = EngineProfile::builtin(GovernorState::GodMode);
+        let conscientious = EngineProfile::builtin(GovernorState::Conscientious);
+        assert_eq!(conscientious.model, god.model);
+        assert!(conscientious.context_length < god.context_length && conscientious.batch_size < god.batch_size);
+        assert_eq!(conscientious.background, BackgroundBudget::Throttled);
+
+        // The old layer count is refused rather than ignored
+        let json = serde_json::to_value(&god).unwrap();
+        assert_eq!(json["placement"], "gpu");
+        let mut legacy = json;
+        legacy["gpu_layers"] = serde_json::json!(16);
+        assert!(serde_json::from_value::<EngineProfile>(legacy).is_err());
+    }
+
+    #[test]
+    fn hold_applies_at_once_and_expires() {
+        let mut governor = Governor::new();
+        governor.set_logging(false);
+        let t0 = Instant::now();
+
+        governor.hold_at(GovernorState::SidekickMode, Duration::from_secs(60), t0);
+        assert_eq!(governor.decide_state_at(&inputs(8.0), t0), GovernorState::SidekickMode);
+        assert_eq!(governor.reason(), "hold");
+
+        // Still yields to the out-of-memory guard
+        assert_eq!(governor.decide_state_at(&inputs(1.0), t0 + Duration::from_secs(1)), GovernorState::PotatoMode);
+
+        // Expired: back under the policy, which upgrades after the usual dwell
+        let later = t0 + Duration::from_secs(61);
+        assert_eq!(governor.decide_state_at(&inputs(8.0), later), GovernorState::PotatoMode);
+        assert_eq!(governor.reason(), "default");
+        assert_eq!(governor.decide_state_at(&inputs(8.0), later + config::GOVERNOR_UPGRADE_DWELL), GovernorState::GodMode);
+    }
+
+    #[test]
+    fn holding_again_extends_up_to_the_cap() {
+        let mut governor = Governor::new();
+        governor.set_logging(false);
+        let t0 = Instant::now();
+
+        governor.hold_at(GovernorState::PotatoMode, config::GOVERNOR_HOLD_STEP, t0);
+        governor.hold_at(GovernorState::PotatoMode, config::GOVERNOR_HOLD_STEP, t0);
+        assert_eq!(governor.hold.unwrap().until, t0 + 2 * config::GOVERNOR_HOLD_STEP);
+
+        for _ in 0..20 {
+            governor.hold_at(GovernorState::PotatoMode, config::GOVERNOR_HOLD_STEP, t0);
+        }
+        assert_eq!(governor.hold.unwrap().until, t0 + config::GOVERNOR_HOLD_MAX);
+
+        // A different state starts over
+        governor.hold_at(GovernorState::GodMode, config::GOVERNOR_HOLD_STEP, t0);
+        assert_eq!(governor.hold.unwrap(), Hold { state: GovernorState::GodMode, until: t0 + config::GOVERNOR_HOLD_STEP });
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::config;
+use crate::classify::{ClassRule, Pattern, ProcessMatch};
+use crate::lobotomy::AppCategory;
+
+/// Classification rules from the game library are named `library:<launcher>:<title>`.
+pub const RULE_PREFIX: &str = "library:";
+
+/// One installed game and where it lives.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Install {
+    pub launcher: &'static str,
+    pub title: String,
+    pub dir: PathBuf,
+}
+
+/// THE GAME SHELF
+/// Installed games as the launchers themselves record them: Steam's library
+/// folders and app manifests, Lutris' game database and Heroic's installed
+/// lists. Local files only; a launcher that isn't there contributes nothing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameLibrary {
+    pub installs: Vec<Install>,
+}
+
+impl GameLibrary {
+    /// Scan the usual (native and Flatpak) launcher locations under `home`.
+    pub fn scan(home: &Path) -> Self {
+        let mut installs = BTreeSet::new();
+        // ~/.steam/steam usually links to ~/.local/share/Steam: read each library once
+        let mut libraries = BTreeSet::new();
+        for root in config::STEAM_ROOTS {
+            for library in steam_libraries(&home.join(root)) {
+                if let Ok(library) = library.canonicalize() {
+                    libraries.insert(library);
+                }
+            }
+        }
+        for library in &libraries {
+            installs.extend(steam_installs(library));
+        }
+        for db in config::LUTRIS_DBS {
+            installs.extend(lutris_installs(&home.join(db)));
+        }
+        for dir in config::HEROIC_DIRS {
+            installs.extend(heroic_installs(&home.join(dir)));
+        }
+
+        let installs = installs.into_iter().filter(|i| plausible(&i.dir, home)).collect();
+        Self { installs }
+    }
+
+    pub fn count(&self, launcher: &str) -> usize {
+        self.installs.iter().filter(|i| i.launcher == launcher).count()
+    }
+
+    /// Two rules per install: the executable lives in the install dir (native
+    /// games), or a Wine/Proton launch runs an `.exe` from it (`wine64-preloader
+    /// Z:\...\game.exe`, with the Windows path separators). The second needs the
+    /// launcher among the ancestors, so `du` or an editor opened on the folder
+    /// doesn't count as a game.
+    pub fn rules(&self) -> Vec<ClassRule> {
+        let mut rules = Vec::new();
+        for install in &self.installs {
+            let dir = dir_regex(&install.dir);
+            let name = format!("{}{}:{}", RULE_PREFIX, install.launcher, install.title);
+            let matchers = [
+                ProcessMatch { exe: pattern(&format!("re:^{}/", dir)), ..Default::default() },
+                ProcessMatch {
+                    cmdline: pattern(&format!("re:(?i){}[/\\\\].*\\.exe\\b", dir)),
+                    ancestor: pattern(config::WINE_LAUNCH_ANCESTORS),
+                    ..Default::default()
+                },
+            ];
+            for when in matchers {
+                rules.push(ClassRule {
+                    name: name.clone(),
+                    priority: config::LIBRARY_RULE_PRIORITY,
+                    when,
+                    category: Some(AppCategory::Game),
+                    launcher: None,
+                });
+            }
+        }
+        rules
+    }
+}
+
+/// Scan `home` now and every `interval` after, off the caller's thread.
+/// Only scans that differ from the previous one are sent.
+pub fn watch(home: PathBuf, interval: Duration) -> Receiver<GameLibrary> {
+    let (tx, rx) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name("cartesian-library".to_string())
+        .spawn(move || {
+            let mut last = None;
+            loop {
+                let library = GameLibrary::scan(&home);
+                if last.as_ref() != Some(&library) {
+                    last = Some(library.clone());
+                    if tx.send(library).is_err() {
+                        return;
+                    }
+                }
+                thread::sleep(interval);
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("Lobotomy: Game library scans disabled: {}", e);
+    }
+    rx
+}
+
+fn pattern(source: &str) -> Option<Pattern> {
+    Pattern::try_from(source.to_string()).ok()
+}
+
+/// The directory as a regex, either path separator allowed between components.
+fn dir_regex(dir: &Path) -> String {
+    dir.to_string_lossy()
+        .trim_end_matches('/')
+        .split('/')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join("[/\\\\]")
+}
+
+/// A launcher that records the home directory (or `/`) as a game's folder
+/// would make everything a game.
+fn plausible(dir: &Path, home: &Path) -> bool {
+    dir.is_absolute() && !home.starts_with(dir) && dir.components().count() > 2
+}
+
+// --- STEAM ---
+
+/// Library folders listed in a Steam root's libraryfolders.vdf, the root itself included.
+fn steam_libraries(root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![root.to_path_buf()];
+    let Ok(data) = fs::read_to_string(root.join("steamapps/libraryfolders.vdf")) else { return libraries; };
+    let Ok(vdf) = parse_vdf(&data) else {
+        eprintln!("Lobotomy: Unreadable {:?}", root.join("steamapps/libraryfolders.vdf"));
+        return libraries;
+    };
+    if let Some(Vdf::Map(folders)) = vdf.get("libraryfolders") {
+        for (_, folder) in folders {
+            if let Some(Vdf::Str(path)) = folder.get("path") {
+                libraries.push(PathBuf::from(path));
+            }
+        }
+    }
+    libraries
+}
+
+/// Games installed in one library, from its `steamapps/appmanifest_*.acf`.
+fn steam_installs(library: &Path) -> Vec<Install> {
+    let steamapps = library.join("steamapps");
+    let Ok(entries) = fs::read_dir(&steamapps) else { return Vec::new(); };
+    entries.flatten()
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("appmanifest_") && name.ends_with(".acf")
+        })
+        .filter_map(|e| {
+            let vdf = parse_vdf(&fs::read_to_string(e.path()).ok()?).ok()?;
+            let app = vdf.get("AppState")?;
+            let Some(Vdf::Str(installdir)) = app.get("installdir") else { return None; };
+            let title = match app.get("name") {
+                Some(Vdf::Str(name)) => name.clone(),
+                _ => installdir.clone(),
+            };
+            Some(Install { launcher: "steam", title, dir: steamapps.join("common").join(installdir) })
+        })
+        .collect()
+}
+
+/// A Valve KeyValues (VDF/ACF) value: a string or a nested block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Vdf {
+    Str(String),
+    Map(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+    /// The first value under `key` (keys compare case-insensitively, as Steam does).
+    pub fn get(&self, key: &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Map(entries) => entries.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v),
+            Vdf::Str(_) => None,
+        }
+    }
+}
+
+/// Parse a text VDF document into its top-level block.
+pub fn parse_vdf(data: &str) -> Result<Vdf, String> {
+    let mut tokens = Vec::new();
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => tokens.push(c.to_string()),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(other) => s.push(other),
+                            None => return Err("unterminated string".to_string()),
+                        },
+                        Some(other) => s.push(other),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(format!("\"{}", s)); // Marked so a quoted "{" stays a string
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next().is_some_and(|c| c != '\n') {}
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                let mut s = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '{' || next == '}' || next == '"' { break; }
+                    s.push(next);
+                    chars.next();
+                }
+                tokens.push(format!("\"{}", s));
+            }
+        }
+    }
+
+    let mut tokens = tokens.into_iter();
+    let block = parse_block(&mut tokens, false)?;
+    Ok(block)
+}
+
+fn parse_block(tokens: &mut impl Iterator<Item = String>, nested: bool) -> Result<Vdf, String> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some(t) if t == "}" && nested => return Ok(Vdf::Map(entries)),
+            Some(t) if t == "}" || t == "{" => return Err(format!("unexpected '{}'", t)),
+            Some(t) => t[1..].to_string(),
+            None if nested => return Err("unterminated block".to_string()),
+            None => return Ok(Vdf::Map(entries)),
+        };
+        let value = match tokens.next() {
+            Some(t) if t == "{" => parse_block(tokens, true)?,
+            Some(t) if t == "}" => return Err(format!("'{}' has no value", key)),
+            Some(t) => Vdf::Str(t[1..].to_string()),
+            None => return Err(format!("'{}' has no value", key)),
+        };
+        entries.push((key, value));
+    }
+}
+
+// --- LUTRIS ---
+
+/// Installed games with a directory in Lutris' pga.db.
+fn lutris_installs(db: &Path) -> Vec<Install> {
+    if !db.exists() {
+        return Vec::new();
+    }
+    let read = || -> rusqlite::Result<Vec<Install>> {
+        let conn = rusqlite::Connection::open_with_flags(db, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut stmt = conn.prepare(
+            "SELECT name, directory FROM games WHERE installed = 1 AND directory IS NOT NULL AND directory != ''"
+        )?;
+        let rows = stmt.query_map([], |row| Ok(Install {
+            launcher: "lutris",
+            title: row.get(0)?,
+            dir: PathBuf::from(row.get::<_, String>(1)?),
+        }))?;
+        rows.collect()
+    };
+    read().unwrap_or_else(|e| {
+        eprintln!("Lobotomy: Unreadable Lutris database {:?}: {}", db, e);
+        Vec::new()
+    })
+}
+
+// --- HEROIC ---
+
+/// Epic (legendary), GOG and Amazon (nile) installs from Heroic's config dir.
+fn heroic_installs(dir: &Path) -> Vec<Install> {
+    let read = |path: &str| -> Option<serde_json::Value> {
+        serde_json::from_str(&fs::read_to_string(dir.join(path)).ok()?).ok()
+    };
+    let text = |value: &serde_json::Value, key: &str| value.get(key)?.as_str().map(str::to_string);
+    let mut installs = Vec::new();
+
+    // { "<app name>": { "title": ..., "install_path": ... } }
+    if let Some(serde_json::Value::Object(apps)) = read("legendaryConfig/legendary/installed.json") {
+        for (app, entry) in &apps {
+            if let Some(path) = text(entry, "install_path") {
+                installs.push(Install { launcher: "heroic", title: text(entry, "title").unwrap_or(app.clone()), dir: path.into() });
+            }
+        }
+    }
+    // { "installed": [{ "appName": ..., "install_path": ... }] }
+    if let Some(games) = read("gog_store/installed.json").and_then(|v| v.get("installed")?.as_array().cloned()) {
+        for entry in &games {
+            if let (Some(app), Some(path)) = (text(entry, "appName"), text(entry, "install_path")) {
+                installs.push(Install { launcher: "heroic", title: app, dir: path.into() });
+            }
+        }
+    }
+    // [{ "id": ..., "path": ... }]
+    if let Some(serde_json::Value::Array(games)) = read("nile_config/nile/installed.json") {
+        for entry in &games {
+            if let (Some(id), Some(path)) = (text(entry, "id"), text(entry, "path")) {
+                installs.push(Install { launcher: "heroic", title: id, dir: path.into() });
+            }
+        }
+    }
+    installs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classify::{Class, Classifier, ProcessFacts};
+
+    fn temp_home(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cartesian-library-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, data: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn vdf_parses() {
+        let vdf = parse_vdf(r#"
+            // Written by Steam
+            "AppState"
+            {
+                "appid"     "1145360"
+                "name"      "Hades \"Deluxe\""
+                "InstallDir"    "Hades"
+                "UserConfig" { "language" "english" }
+            }
+        "#).unwrap();
+        let app = vdf.get("appstate").unwrap();
+        assert_eq!(app.get("name"), Some(&Vdf::Str("Hades \"Deluxe\"".to_string())));
+        assert_eq!(app.get("installdir"), Some(&Vdf::Str("Hades".to_string())));
+        assert!(matches!(app.get("UserConfig"), Some(Vdf::Map(_))));
+
+        assert!(parse_vdf(r#""AppState" { "name" "x""#).is_err());
+        assert!(parse_vdf(r#""AppState" }"#).is_err());
+    }
+
+    #[test]
+    fn launchers_are_scanned() {
+        let home = temp_home("scan");
+        let steam = home.join(".local/share/Steam");
+        let extra = home.join("drive/SteamLibrary");
+        write(&steam.join("steamapps/libraryfolders.vdf"), &format!(
+            "\"libraryfolders\" {{ \"0\" {{ \"path\" \"{}\" }} \"1\" {{ \"path\" \"{}\" }} }}",
+            steam.display(), extra.display(),
+        ));
+        write(&steam.join("steamapps/appmanifest_570.acf"), r#""AppState" { "name" "Dota 2" "installdir" "dota 2 beta" }"#);
+        write(&extra.join("steamapps/appmanifest_1145360.acf"), r#""AppState" { "name" "Hades" "installdir" "Hades" }"#);
+        write(&home.join(".config/heroic/legendaryConfig/legendary/installed.json"),
+            r#"{ "Fortnite": { "title": "Fortnite", "install_path": "/games/epic/Fortnite" } }"#);
+        write(&home.join(".config/heroic/gog_store/installed.json"),
+            r#"{ "installed": [{ "appName": "1207658924", "install_path": "/games/gog/Witcher" }] }"#);
+
+        let lutris = home.join(".local/share/lutris/pga.db");
+        fs::create_dir_all(lutris.parent().unwrap()).unwrap();
+        let conn = rusqlite::Connection::open(&lutris).unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE games (name TEXT, directory TEXT, installed INTEGER);
+             INSERT INTO games VALUES ('Celeste', '/games/lutris/celeste', 1);
+             INSERT INTO games VALUES ('Removed', '/games/lutris/removed', 0);
+             INSERT INTO games VALUES ('Everything', '{}', 1);",
+            home.display(),
+        )).unwrap();
+        drop(conn);
+
+        let library = GameLibrary::scan(&home);
+        let titles: Vec<&str> = library.installs.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(library.count("steam"), 2, "{:?}", titles);
+        assert_eq!(library.count("lutris"), 1, "{:?}", titles);
+        assert_eq!(library.count("heroic"), 2, "{:?}", titles);
+        let hades = library.installs.iter().find(|i| i.title == "Hades").unwrap();
+        assert_eq!(hades.dir, extra.canonicalize().unwrap().join("steamapps/common/Hades"));
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn processes_inside_an_install_are_games() {
+        let library = GameLibrary {
+            installs: vec![Install { launcher: "steam", title: "Hades".to_string(), dir: "/home/u/Steam/steamapps/common/Hades".into() }],
+        };
+        let classifier = Classifier::builtin().extended(library.rules());
+        let process = |exe: &str, cmdline: &str| ProcessFacts {
+            name: "x".to_string(),
+            exe: Some(exe.to_string()),
+            cmdline: cmdline.to_string(),
+            ..Default::default()
+        };
+        // Heroic isn't a known game parent; only the library knows this one
+        let from_heroic = |exe: &str, cmdline: &str| ProcessFacts {
+            ancestors: vec!["umu-run".to_string(), "heroic".to_string()],
+            ..process(exe, cmdline)
+        };
+        let from_shell = |exe: &str, cmdline: &str| ProcessFacts {
+            ancestors: vec!["bash".to_string(), "konsole".to_string()],
+            ..process(exe, cmdline)
+        };
+        let class = |p: &ProcessFacts| classifier.classify(p).map(|(class, _)| class);
+
+        let native = process("/home/u/Steam/steamapps/common/Hades/Hades", "");
+        assert_eq!(class(&native), Some(Class::Category(AppCategory::Game)));
+        let proton = from_heroic(
+            "/home/u/Steam/steamapps/common/Proton 9.0/files/bin/wine64-preloader",
+            r"Z:\home\u\Steam\steamapps\common\Hades\x64\Hades.exe",
+        );
+        assert_eq!(class(&proton), Some(Class::Category(AppCategory::Game)));
+        // A sibling whose name merely starts the same
+        assert_eq!(class(&process("/home/u/Steam/steamapps/common/Hades II/Hades2", "")), None);
+
+        // Tools pointed at the folder are not the game
+        assert_eq!(class(&from_shell("/usr/bin/du", "du -sh /home/u/Steam/steamapps/common/Hades/")), None);
+        assert_eq!(class(&from_shell("/usr/bin/rm", "rm /home/u/Steam/steamapps/common/Hades/x64/Hades.exe")), None);
+        assert_eq!(class(&from_shell("/usr/bin/wine", "wine /home/u/Steam/steamapps/common/Hades/x64/Hades.exe")), None);
+    }
+}
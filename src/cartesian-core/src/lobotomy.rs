@@ -1,11 +1,22 @@
-use sysinfo::{Pid, System, ProcessesToUpdate};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::Instant;
 use crate::config; 
+use crate::categories;
+use crate::classify::{self, Class, Classifier, ProcessFacts};
+use crate::accounting::{SelfAccountant, SelfUsage};
+use crate::inference::GovernorState;
+use crate::pressure::{PsiReader, PsiSnapshot};
+use crate::library::{self, GameLibrary};
+use crate::registry::{self, Registry};
+use crate::procwatch::{self, ProcessSource};
+use crate::sensors::{PowerReading, Sensors};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[derive(Serialize, Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[serde(into = "String")]
 pub enum AppCategory {
     Game,        
     Production,  
@@ -14,90 +25,331 @@ pub enum AppCategory {
     Media,       
     System,      
     Unknown,     
+    Custom(&'static str), // Defined in categories.json (see categories.rs)
 }
 
+impl AppCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppCategory::Game => "Game",
+            AppCategory::Production => "Production",
+            AppCategory::Development => "Development",
+            AppCategory::Web => "Web",
+            AppCategory::Media => "Media",
+            AppCategory::System => "System",
+            AppCategory::Unknown => "Unknown",
+            AppCategory::Custom(name) => name,
+        }
+    }
+}
+
+impl TryFrom<String> for AppCategory {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, String> {
+        CATEGORIES.into_iter().chain([AppCategory::Unknown])
+            .find(|c| c.name() == name)
+            .or_else(|| categories::lookup(&name).map(AppCategory::Custom))
+            .ok_or_else(|| format!("unknown category '{}'", name))
+    }
+}
+
+// By hand: a derived impl would tie the input's lifetime to the `&'static str`.
+// A name categories.json no longer defines reads as Unknown, so one stale
+// registry entry or trace record doesn't reject the whole file.
+impl<'de> Deserialize<'de> for AppCategory {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(AppCategory::try_from(name.clone()).unwrap_or_else(|_| {
+            categories::warn_undefined(&name);
+            AppCategory::Unknown
+        }))
+    }
+}
+
+impl From<AppCategory> for String {
+    fn from(category: AppCategory) -> Self {
+        category.name().to_string()
+    }
+}
+
+/// A sizeable app no rule or registry entry knows, summed over its processes.
+#[derive(Debug, Clone)]
+pub struct UnknownApp {
+    pub name: String,
+    pub exe: Option<String>,
+    pub memory_bytes: u64,
+    pub cpu_percent: f32,
+    pub suggestion: Option<AppCategory>, // From the name/path hints
+}
+
+/// What one category is using on a tick, summed over its processes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CategoryUsage {
+    pub cpu_percent: f32, // Per-core percent, like top (can exceed 100)
+    pub memory_bytes: u64,
+    pub gpu_bytes: u64,
+    pub focused: bool,
+}
+
+/// Outside knowledge for a scan: the focused window's process (and whether
+/// it is fullscreen) and GPU memory per pid, where available.
+#[derive(Debug, Clone, Default)]
+pub struct ContextHints {
+    pub focused_pid: Option<u32>,
+    pub focused_fullscreen: bool,
+    pub gpu_bytes: HashMap<u32, u64>,
+}
+
+/// The scan's verdict: every category doing real work, strongest first.
+#[derive(Debug, Clone)]
+pub struct ContextReport {
+    pub dominant: AppCategory,
+    pub active: Vec<(AppCategory, f32)>, // Smoothed score, descending
+    pub unknown_apps: Vec<UnknownApp>,
+    /// The strictest registry `max_state` among running apps, and whose it is.
+    pub ceiling: Option<(String, GovernorState)>,
+}
+
+pub const CATEGORIES: [AppCategory; 6] = [
+    AppCategory::Game, AppCategory::Production, AppCategory::Development,
+    AppCategory::Web, AppCategory::Media, AppCategory::System,
+];
+
 pub struct SystemMonitor {
     sys: System,
     cached_pid: Option<Pid>,
-    registry: HashMap<String, AppCategory>,
+    registry: Registry,
+    library: GameLibrary,
+    library_scans: Receiver<GameLibrary>, // From the scanner thread
+    rules: Classifier, // Built-in and process_rules.json, before the registry's and the library's
+    classifier: Classifier,
+    // Rule verdicts (class, rule name) by pid; the start time tells a reused pid apart
+    verdicts: HashMap<Pid, (u64, Option<(Class, String)>)>,
+    proc_source: Box<dyn ProcessSource>,
+    last_rescan: Option<Instant>,
+    // Processes that counted in the last scan; only these are re-read between rescans
+    tracked: HashSet<Pid>,
+    context_scores: HashMap<AppCategory, f32>, // Smoothed across scans
+    psi: Option<PsiReader>,
+    sensors: Sensors,
+    active_launchers: Vec<String>,
+    accountant: SelfAccountant,
 }
 
 impl SystemMonitor {
     pub fn new() -> Self {
-        let mut monitor = Self {
+        let rules = Classifier::load(Path::new(&config::get_process_rules_path())).unwrap_or_else(|e| {
+            eprintln!("Lobotomy: Process rules rejected, using built-in rules: {}", e);
+            Classifier::builtin()
+        });
+        let registry = Registry::open(
+            PathBuf::from(config::get_registry_path()),
+            PathBuf::from(config::SYSTEM_REGISTRY_PATH),
+            Path::new(config::LEGACY_REGISTRY_PATH),
+        );
+        let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+        let monitor = Self {
             sys: System::new_all(),
             cached_pid: None,
-            registry: HashMap::new(),
+            classifier: rules.extended(registry.rules()),
+            registry,
+            library: GameLibrary::default(),
+            library_scans: library::watch(home, config::LIBRARY_RESCAN_INTERVAL),
+            rules,
+            verdicts: HashMap::new(),
+            proc_source: procwatch::detect(),
+            last_rescan: None,
+            tracked: HashSet::new(),
+            context_scores: HashMap::new(),
+            psi: PsiReader::detect(),
+            sensors: Sensors::new(),
+            active_launchers: Vec::new(),
+            accountant: SelfAccountant::new(),
         };
-        monitor.load_registry();
+        println!("Lobotomy: Process events from {}.", monitor.proc_source.name());
+        if let Some(psi) = &monitor.psi {
+            println!("Lobotomy: Reading pressure stall info ({}).", psi.scope());
+        }
         monitor
     }
 
-    fn load_registry(&mut self) {
-        if Path::new(config::REGISTRY_PATH).exists() {
-            if let Ok(data) = fs::read_to_string(config::REGISTRY_PATH) {
-                if let Ok(records) = serde_json::from_str::<HashMap<String, AppCategory>>(&data) {
-                    self.registry = records;
-                    return;
-                }
-            }
+    /// The user's answer for an unknown app. Applies from the next scan.
+    pub fn learn(&mut self, name: &str, category: AppCategory) -> Result<(), String> {
+        self.registry.learn(name, category)
+    }
+
+    /// Pick up registry edits and games installed or removed since the last
+    /// library scan (scans run on their own thread); every process is
+    /// classified again under the new rules.
+    fn reload_rules(&mut self) {
+        let registry_changed = match self.registry.poll() {
+            Some(Ok(())) => { println!("Lobotomy: Registry reloaded."); true }
+            Some(Err(e)) => { eprintln!("Lobotomy: {}", e); true }
+            None => false,
+        };
+        let mut library_changed = false;
+        while let Ok(library) = self.library_scans.try_recv() {
+            println!(
+                "Lobotomy: Game library: {} Steam, {} Lutris, {} Heroic installs.",
+                library.count("steam"), library.count("lutris"), library.count("heroic"),
+            );
+            self.library = library;
+            library_changed = true;
+        }
+        if !registry_changed && !library_changed {
+            return;
         }
-        self.registry.insert("firefox".into(), AppCategory::Web);
+        self.classifier = self.rules.extended(self.registry.rules().into_iter().chain(self.library.rules()).collect());
+        self.verdicts.clear();
     }
 
-    pub fn save_registry(&self) {
-        if let Ok(data) = serde_json::to_string_pretty(&self.registry) {
-            let _ = fs::write(config::REGISTRY_PATH, data);
+    /// Bring the process table up to date with what the process source saw:
+    /// new processes are read in full, exec'd ones re-read (and reclassified),
+    /// exited ones dropped. Everything is rescanned every
+    /// `PROC_RESCAN_INTERVAL`, or at once if events were lost.
+    /// Returns whether that was a full rescan.
+    fn apply_process_changes(&mut self) -> bool {
+        let changes = self.proc_source.drain();
+        // Command lines feed the classification rules
+        let kind = ProcessRefreshKind::nothing()
+            .with_memory()
+            .with_cpu()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet);
+
+        let due = self.last_rescan.is_none_or(|at| at.elapsed() >= config::PROC_RESCAN_INTERVAL);
+        if changes.lost || due {
+            self.sys.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+            self.last_rescan = Some(Instant::now());
+            return true;
+        }
+
+        let execed: Vec<Pid> = changes.execed.iter().map(|pid| Pid::from_u32(*pid)).collect();
+        for pid in &execed {
+            self.verdicts.remove(pid);
         }
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&execed),
+            true,
+            kind.with_exe(UpdateKind::Always).with_cmd(UpdateKind::Always),
+        );
+
+        // Exited pids aren't found, so the refresh removes them
+        let churn: Vec<Pid> = changes.started.iter().chain(&changes.exited).map(|pid| Pid::from_u32(*pid)).collect();
+        self.sys.refresh_processes_specifics(ProcessesToUpdate::Some(&churn), true, kind);
+        false
     }
 
-    pub fn get_system_context(&mut self) -> (AppCategory, Vec<String>) {
-        self.sys.refresh_processes(ProcessesToUpdate::All, true);
-        
-        let mut active_categories = HashMap::new();
-        let mut unknown_apps = Vec::new();
+    pub fn get_system_context(&mut self, hints: &ContextHints) -> ContextReport {
+        self.reload_rules();
+        if !self.apply_process_changes() {
+            // Usage only moves for processes that count; the rest wait for the rescan
+            let pids: Vec<Pid> = self.tracked.iter().copied()
+                .chain(hints.focused_pid.map(Pid::from_u32))
+                .collect();
+            self.sys.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&pids),
+                true,
+                ProcessRefreshKind::nothing().with_memory().with_cpu(),
+            );
+        }
+        let processes = self.sys.processes();
+        self.verdicts.retain(|pid, _| processes.contains_key(pid));
+
+        let mut usage: HashMap<AppCategory, CategoryUsage> = HashMap::new();
+        let mut unknown_apps: HashMap<String, UnknownApp> = HashMap::new();
+        let mut unknown_pids: HashMap<String, Vec<Pid>> = HashMap::new();
+        let mut tracked = HashSet::new();
+        let mut ceiling: Option<(String, GovernorState)> = None;
+        self.active_launchers.clear();
+
+        for (pid, process) in processes {
+            // A process keeps its verdict for life: /proc is only read for new ones
+            let verdict = match self.verdicts.get(pid) {
+                Some((started, verdict)) if *started == process.start_time() => verdict.clone(),
+                _ => {
+                    let verdict = self.classifier.classify(&facts(&self.sys, process))
+                        .map(|(class, rule)| (class, rule.to_string()));
+                    self.verdicts.insert(*pid, (process.start_time(), verdict.clone()));
+                    verdict
+                }
+            };
 
-        for (_pid, process) in self.sys.processes() {
-            // FIXED: Convert OsString to String immediately using lossy conversion
-            // This fixes E0599 (no method contains) and E0308 (mismatched types)
             let name = process.name().to_string_lossy().to_ascii_lowercase();
-            
-            if config::GAMES.iter().any(|&g| name.contains(g)) {
-                *active_categories.entry(AppCategory::Game).or_insert(0) += 1;
-                continue;
-            }
-            if config::CREATIVE_SUITE.iter().any(|&c| name.contains(c)) {
-                *active_categories.entry(AppCategory::Production).or_insert(0) += 1;
-                continue;
-            }
-            if config::DEV_TOOLS.iter().any(|&d| name.contains(d)) {
-                *active_categories.entry(AppCategory::Development).or_insert(0) += 1;
-                continue;
+            let focused = hints.focused_pid == Some(pid.as_u32());
+            let fullscreen = focused && hints.focused_fullscreen;
+            let gpu_bytes = hints.gpu_bytes.get(&pid.as_u32()).copied().unwrap_or(0);
+            // The registry entry behind a registry rule, else the one for this name
+            let entry = match verdict.as_ref().and_then(|(_, rule)| rule.strip_prefix(registry::RULE_PREFIX)) {
+                Some(app) => self.registry.get(app),
+                None => self.registry.get(&name),
+            };
+            if let Some(max) = entry.and_then(|e| e.max_state) {
+                if ceiling.as_ref().is_none_or(|(_, strictest)| max.rank() < strictest.rank()) {
+                    ceiling = Some((name.clone(), max));
+                }
             }
 
-            match self.registry.get(&name) {
-                Some(cat) => {
-                    *active_categories.entry(*cat).or_insert(0) += 1;
+            let category = match verdict.map(|(class, _)| class) {
+                Some(Class::Launcher(launcher)) => {
+                    if !self.active_launchers.contains(&launcher) {
+                        self.active_launchers.push(launcher);
+                    }
+                    tracked.insert(*pid);
+                    continue;
+                }
+                Some(Class::Category(category)) => Some(category),
+                None => entry.map(|e| e.category)
+                    .or(fullscreen_game(fullscreen, gpu_bytes).then_some(AppCategory::Game)),
+            };
+
+            match category {
+                Some(category) => {
+                    tracked.insert(*pid);
+                    let entry = usage.entry(category).or_default();
+                    entry.cpu_percent += process.cpu_usage();
+                    entry.memory_bytes += process.memory();
+                    entry.gpu_bytes += gpu_bytes;
+                    entry.focused |= focused;
                 },
                 None => {
-                    if process.memory() > 50_000_000 { 
-                        unknown_apps.push(name.clone());
-                    }
+                    unknown_pids.entry(name.clone()).or_default().push(*pid);
+                    let app = unknown_apps.entry(name.clone()).or_insert_with(|| UnknownApp {
+                        exe: process.exe().map(|e| e.to_string_lossy().to_string()),
+                        // A fullscreen terminal, PDF or video is only asked about
+                        suggestion: classify::suggest(&name, process.exe())
+                            .or(fullscreen.then_some(AppCategory::Game)),
+                        name,
+                        memory_bytes: 0,
+                        cpu_percent: 0.0,
+                    });
+                    app.memory_bytes += process.memory();
+                    app.cpu_percent += process.cpu_usage();
                 }
             }
         }
 
-        let dominant = if active_categories.contains_key(&AppCategory::Game) {
-            AppCategory::Game
-        } else if active_categories.contains_key(&AppCategory::Production) {
-            AppCategory::Production
-        } else if active_categories.contains_key(&AppCategory::Development) {
-            AppCategory::Development
-        } else {
-            AppCategory::System
-        };
+        // Helpers and tabs add up: an app counts once its processes pass the threshold together
+        let mut unknown_apps: Vec<UnknownApp> = unknown_apps.into_values()
+            .filter(|app| app.memory_bytes > config::UNKNOWN_APP_MIN_BYTES)
+            .collect();
+        unknown_apps.sort_by_key(|app| std::cmp::Reverse(app.memory_bytes));
+        tracked.extend(unknown_apps.iter().flat_map(|app| unknown_pids.remove(&app.name).unwrap_or_default()));
+        self.tracked = tracked;
 
-        (dominant, unknown_apps)
+        let active = smooth(&mut self.context_scores, &weigh(&usage));
+        ContextReport {
+            dominant: active.first().map_or(AppCategory::System, |(category, _)| *category),
+            active,
+            unknown_apps,
+            ceiling,
+        }
+    }
+
+    /// Launchers seen by the last `get_system_context` scan, by signal name.
+    pub fn active_launchers(&self) -> &[String] {
+        &self.active_launchers
     }
 
     pub fn get_vitals(&mut self) -> (f32, f32) {
@@ -107,10 +359,32 @@ impl SystemMonitor {
         let ram = self.sys.available_memory() as f32 / 1_073_741_824.0;
         (cpu, ram)
     }
+
+    /// CPU/memory/IO stall averages, None where the kernel has no PSI.
+    pub fn get_pressure(&self) -> Option<PsiSnapshot> {
+        self.psi.as_ref()?.read()
+    }
+
+    pub fn get_power(&self) -> Option<PowerReading> {
+        self.sensors.read_power()
+    }
+
+    /// cartesian-core's own footprint, CPU split by subsystem. `gpu_bytes`
+    /// is our entry in the VRAM probe's per-process list.
+    pub fn get_self_usage(&mut self, gpu_bytes: Option<u64>) -> SelfUsage {
+        self.accountant.sample(gpu_bytes)
+    }
+
+    /// Hottest thermal zone, °C.
+    pub fn get_temperature(&self) -> Option<f32> {
+        self.sensors.read_temperature()
+    }
     
+    /// Searches the table the context scans keep; only pending process
+    /// events are applied, never a scan of its own.
     pub fn find_process(&mut self, name: &str) -> Option<Pid> {
+        self.apply_process_changes();
         if let Some(pid) = self.cached_pid {
-            self.sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
             if self.sys.processes().contains_key(&pid) {
                 return Some(pid);
             } else {
@@ -118,7 +392,6 @@ impl SystemMonitor {
             }
         }
 
-        self.sys.refresh_processes(ProcessesToUpdate::All, true);
         for (pid, process) in self.sys.processes() {
             // FIXED: name() -> to_string_lossy() -> to_ascii_lowercase
             if process.name().to_string_lossy().to_ascii_lowercase().contains(&name.to_ascii_lowercase()) {
@@ -128,4 +401,154 @@ impl SystemMonitor {
         }
         None
     }
-}
\ No newline at end of file
+}
+
+/// A fullscreen window is a game only while it holds the GPU as well.
+fn fullscreen_game(fullscreen: bool, gpu_bytes: u64) -> bool {
+    fullscreen && gpu_bytes >= config::FULLSCREEN_GAME_MIN_GPU_BYTES
+}
+
+/// Score each busy category by its share of CPU, memory and GPU memory among
+/// the busy ones, plus a bonus for holding focus. Categories that are idle
+/// (little CPU, no GPU memory, not focused) don't compete at all, so a
+/// launcher or editor parked in the background can't decide the context.
+pub fn weigh(usage: &HashMap<AppCategory, CategoryUsage>) -> HashMap<AppCategory, f32> {
+    let busy: Vec<(&AppCategory, &CategoryUsage)> = usage.iter()
+        .filter(|(_, u)| u.cpu_percent >= config::CONTEXT_IDLE_CPU || u.gpu_bytes > 0 || u.focused)
+        .collect();
+
+    let total_cpu: f32 = busy.iter().map(|(_, u)| u.cpu_percent).sum();
+    let total_memory: u64 = busy.iter().map(|(_, u)| u.memory_bytes).sum();
+    let total_gpu: u64 = busy.iter().map(|(_, u)| u.gpu_bytes).sum();
+    let share = |part: f32, total: f32| if total > 0.0 { part / total } else { 0.0 };
+
+    busy.into_iter()
+        .map(|(category, u)| {
+            let score = config::CONTEXT_WEIGHT_CPU * share(u.cpu_percent, total_cpu)
+                + config::CONTEXT_WEIGHT_MEMORY * share(u.memory_bytes as f32, total_memory as f32)
+                + config::CONTEXT_WEIGHT_GPU * share(u.gpu_bytes as f32, total_gpu as f32)
+                + if u.focused { config::CONTEXT_WEIGHT_FOCUS } else { 0.0 };
+            (*category, score)
+        })
+        .collect()
+}
+
+/// Blend this scan's scores into the running ones (a category missing from
+/// `raw` decays toward zero) and return those above the activity floor, strongest first.
+fn smooth(scores: &mut HashMap<AppCategory, f32>, raw: &HashMap<AppCategory, f32>) -> Vec<(AppCategory, f32)> {
+    // Every category that scored or still has a score to decay; Unknown never competes
+    let seen: HashSet<AppCategory> = scores.keys().chain(raw.keys()).copied()
+        .filter(|c| *c != AppCategory::Unknown)
+        .collect();
+    for category in seen {
+        let target = raw.get(&category).copied().unwrap_or(0.0);
+        let score = scores.entry(category).or_insert(0.0);
+        *score += config::CONTEXT_SMOOTHING * (target - *score);
+    }
+    let mut active: Vec<(AppCategory, f32)> = scores.iter()
+        .filter(|(_, s)| **s >= config::CONTEXT_MIN_SCORE)
+        .map(|(c, s)| (*c, *s))
+        .collect();
+    active.sort_by(|a, b| b.1.total_cmp(&a.1));
+    active
+}
+
+/// What the classification rules see of a process. Ancestors are resolved
+/// through the process table, nearest first.
+fn facts(sys: &System, process: &Process) -> ProcessFacts {
+    let mut ancestors = Vec::new();
+    let mut parent = process.parent();
+    // Depth cap guards against pid reuse forming a loop
+    while let Some(p) = parent.and_then(|pid| sys.process(pid)).filter(|_| ancestors.len() < 64) {
+        ancestors.push(p.name().to_string_lossy().to_ascii_lowercase());
+        parent = p.parent();
+    }
+
+    ProcessFacts {
+        name: process.name().to_string_lossy().to_ascii_lowercase(),
+        exe: process.exe().map(|e| e.to_string_lossy().to_string()),
+        cmdline: process.cmd().iter().map(|a| a.to_string_lossy()).collect::<Vec<_>>().join(" "),
+        ancestors,
+        cgroup: classify::read_cgroup(process.pid().as_u32()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1 << 30;
+
+    fn usage(entries: &[(AppCategory, f32, u64, u64, bool)]) -> HashMap<AppCategory, CategoryUsage> {
+        entries.iter()
+            .map(|&(c, cpu_percent, memory_bytes, gpu_bytes, focused)| (c, CategoryUsage { cpu_percent, memory_bytes, gpu_bytes, focused }))
+            .collect()
+    }
+
+    fn settle(usage: &HashMap<AppCategory, CategoryUsage>) -> Vec<(AppCategory, f32)> {
+        let mut scores = HashMap::new();
+        let mut active = Vec::new();
+        for _ in 0..20 {
+            active = smooth(&mut scores, &weigh(usage));
+        }
+        active
+    }
+
+    fn categories(active: &[(AppCategory, f32)]) -> Vec<AppCategory> {
+        active.iter().map(|(c, _)| *c).collect()
+    }
+
+    #[test]
+    fn fullscreen_alone_is_not_a_game() {
+        assert!(fullscreen_game(true, 2 * GB));
+        // A fullscreen terminal, PDF viewer or video player
+        assert!(!fullscreen_game(true, 0));
+        assert!(!fullscreen_game(true, config::FULLSCREEN_GAME_MIN_GPU_BYTES - 1));
+        assert!(!fullscreen_game(false, 2 * GB));
+    }
+
+    #[test]
+    fn idle_heavyweights_do_not_win() {
+        // Blender open in the background with 3 GB resident, code being edited
+        let active = settle(&usage(&[
+            (AppCategory::Production, 0.2, 3 * GB, 0, false),
+            (AppCategory::Development, 35.0, GB / 2, 0, true),
+        ]));
+        assert_eq!(categories(&active), vec![AppCategory::Development]);
+    }
+
+    #[test]
+    fn web_and_media_can_dominate_and_contexts_combine() {
+        let active = settle(&usage(&[
+            (AppCategory::Development, 12.0, GB, 0, false),
+            (AppCategory::Media, 20.0, GB / 2, 0, true),
+        ]));
+        assert_eq!(categories(&active), vec![AppCategory::Media, AppCategory::Development]);
+
+        let active = settle(&usage(&[(AppCategory::Web, 30.0, 2 * GB, 0, true)]));
+        assert_eq!(categories(&active), vec![AppCategory::Web]);
+    }
+
+    #[test]
+    fn gpu_use_outweighs_cpu_until_focus_moves() {
+        let game = (AppCategory::Game, 40.0, 4 * GB, 6 * GB, false);
+        let active = settle(&usage(&[game, (AppCategory::Development, 120.0, 2 * GB, 0, false)]));
+        assert_eq!(categories(&active), vec![AppCategory::Game, AppCategory::Development]);
+
+        // Alt-tabbed to the editor: it leads, the game stays active
+        let active = settle(&usage(&[game, (AppCategory::Development, 120.0, 2 * GB, 0, true)]));
+        assert_eq!(categories(&active), vec![AppCategory::Development, AppCategory::Game]);
+    }
+
+    #[test]
+    fn a_closed_category_fades_out() {
+        let mut scores = HashMap::new();
+        let busy = usage(&[(AppCategory::Game, 80.0, 4 * GB, 6 * GB, true)]);
+        for _ in 0..20 { smooth(&mut scores, &weigh(&busy)); }
+
+        let mut active = smooth(&mut scores, &HashMap::new());
+        assert_eq!(categories(&active), vec![AppCategory::Game], "one quiet scan is not enough");
+        for _ in 0..20 { active = smooth(&mut scores, &HashMap::new()); }
+        assert!(active.is_empty());
+    }
+}
@@ -6,30 +6,90 @@ mod audio;
 mod ui; 
 mod config; 
 mod schema; 
+mod privacy;
+mod vault;
+mod archive;
+mod cli;
+mod eval;
+mod consolidation;
+mod policy;
+mod vram;
+mod pressure;
+mod sensors;
+mod telemetry;
+mod predictor;
+mod cgroup;
+mod classify;
+mod hyprland;
+mod procwatch;
+mod registry;
+mod accounting;
+mod categories;
+mod library;
+mod resources;
 
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use chrono::{Datelike, Timelike};
 use iced::{Element, Subscription, Task, Theme, time};
-use lobotomy::{SystemMonitor, AppCategory};
-use witness::Eye;
+use lobotomy::{SystemMonitor, AppCategory, ContextHints, UnknownApp};
+use witness::{Eye, VisualCortex, VisualObservation, VisualSampler};
 use hippocampus::MemorySystem;
-use inference::{Governor, GovernorState, Engine, EmbeddingEngine};
+use consolidation::MemoryHandle;
+use inference::{BackgroundBudget, BackgroundJob, Governor, GovernorState, EngineHandle, EngineProfile, EmbeddingEngine};
 use audio::Mixer;
+use categories::{Categories, Effects};
+use policy::{GovernorInputs, PolicyWatcher};
+use schema::ActionSchema;
+use vault::Vault;
+use vram::{VramProbe, VramReading};
+use pressure::PsiSnapshot;
+use sensors::PowerReading;
+use telemetry::{TraceRecord, TraceWriter};
+use predictor::Predictor;
+use cgroup::Group;
+use accounting::{SelfUsage, Subsystem};
+use resources::{Priority, Requester, Work};
 use ui::chat::ChatMessage;
 
 pub fn main() -> iced::Result {
+    // Headless maintenance commands (export/import, ...) skip the dashboard
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     iced::application(Cartesian::init, Cartesian::update, Cartesian::view)
         .subscription(Cartesian::subscription)
         .theme(Cartesian::theme)
         .window(iced::window::Settings {..Default::default()})
+        // Closing goes through Message::Quit, so the store is flushed first
+        .exit_on_close_request(false)
         .run()
 }
 
+/// Learn game launch patterns from the recorded trace, off the UI thread.
+async fn train_predictor() -> Option<Predictor> {
+    tokio::task::spawn_blocking(|| {
+        let files = telemetry::trace_files(&PathBuf::from(config::get_trace_dir()));
+        let records = telemetry::read_trace(&files)
+            .map_err(|e| eprintln!("Governor: Predictor has no history: {}", e))
+            .ok()?;
+        Some(Predictor::train(&records, config::PREDICT_HORIZON, config::PREDICT_MIN_SUPPORT))
+    }).await.ok().flatten()
+}
+
 pub struct Cartesian {
     pub monitor: SystemMonitor,
     pub eye: Eye,
-    pub memory: MemorySystem,
+    pub sampler: VisualSampler,
+    pub memory: MemoryHandle,
     pub governor: Governor,
-    pub engine: Engine,
-    pub embedder: EmbeddingEngine,
+    pub policy_watcher: PolicyWatcher,
+    pub trace: Option<TraceWriter>,
+    pub last_trace_flush: Instant,
+    pub predictor: Option<Predictor>,
+    pub engine: EngineHandle,
     pub mixer: Mixer,
     pub chat_history: Vec<ChatMessage>,
     pub input_value: String,
@@ -37,32 +97,101 @@ pub struct Cartesian {
     pub vision_status: String,
     pub brain_state: String,
     pub current_context: AppCategory,
-    pub debug_override: bool, 
     pub cpu_usage: f32,
     pub free_ram: f32,
-    pub unknown_count: usize,
+    pub pressure: Option<PsiSnapshot>,
+    pub power: Option<PowerReading>,
+    pub temperature_c: Option<f32>,
+    pub vram_probe: Option<Box<dyn VramProbe>>,
+    pub vram: Option<VramReading>,
+    pub unknown_apps: Vec<UnknownApp>,
+    pub hyprland: Option<hyprland::Hyprland>,
+    pub focus: Option<hyprland::FocusedWindow>,
+    pub ceiling: Option<(String, GovernorState)>, // Registry or category hint and who asked
+    pub categories: Categories,
+    pub effects: Effects, // What the active categories ask for
+    pub own_usage: SelfUsage,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Tick,
-    ToggleOverride,
+    Hold(GovernorState),
+    ReleaseHold,
+    Learn(String, AppCategory),
     InputChanged(String),
     SendChat,
+    Quit,
+    Replied(Option<ActionSchema>),
+    FrameObserved(Option<VisualObservation>),
+    Recalled(Vec<String>),
+    PredictorTrained(Option<Predictor>),
 }
 
 impl Cartesian {
     fn init() -> (Self, Task<Message>) {
+        // Before any worker threads exist, so they start inside the layout
+        match cgroup::init() {
+            Ok(confinement) => println!("Confinement: cgroup {:?}", confinement.root()),
+            Err(e) => println!("Confinement: Disabled: {}", e),
+        }
+
         let mut embedder = EmbeddingEngine::new();
         let _ = embedder.init(); 
 
+        let vault = Vault::from_env();
+        let store = PathBuf::from(config::get_memory_path());
+        // An unreadable store is left alone: saving over it would lose every engram
+        let (memory, store) = match MemorySystem::load(&store, vault.as_ref()) {
+            Ok(memory) => (memory, Some(store)),
+            Err(e) => {
+                eprintln!("Hippocampus: Failed to load store, persistence disabled: {}", e);
+                (MemorySystem::new(), None)
+            }
+        };
+        // Indexing and persistence run on the worker so the dashboard never stalls
+        let memory = MemoryHandle::spawn(memory, embedder, store, vault);
+
+        let trace = TraceWriter::open(&PathBuf::from(config::get_trace_dir()), config::TRACE_MAX_BYTES, config::TRACE_KEEP)
+            .map_err(|e| eprintln!("Telemetry: Governor trace disabled: {}", e))
+            .ok();
+
+        let vram_probe = vram::detect();
+        match &vram_probe {
+            Some(probe) => println!("Governor: VRAM probe: {}", probe.name()),
+            None => println!("Governor: No VRAM probe, falling back to context heuristics."),
+        }
+
+        let control = config::get_control_socket_path().map(PathBuf::from)
+            .and_then(|control| resources::serve(&control).map(|()| control));
+        match control {
+            Ok(control) => println!("Resources: Control socket at {:?}", control),
+            Err(e) => eprintln!("Resources: No control socket: {}", e),
+        }
+
+        let hyprland = hyprland::Hyprland::connect();
+        match &hyprland {
+            Some(_) => println!("Lobotomy: Following Hyprland focus."),
+            None => println!("Lobotomy: No Hyprland session, focus is unknown."),
+        }
+
+        // Before the monitor, so custom category names resolve in the registry
+        let categories = Categories::load(Path::new(&config::get_categories_path())).unwrap_or_else(|e| {
+            eprintln!("Lobotomy: Categories rejected, using built-in: {}", e);
+            Categories::builtin()
+        });
+
         (Self {
             monitor: SystemMonitor::new(),
             eye: Eye::new(),
-            memory: MemorySystem::new(),
+            sampler: VisualSampler::new(),
+            memory,
             governor: Governor::new(),
-            engine: Engine::new(),
-            embedder,
+            policy_watcher: PolicyWatcher::new(PathBuf::from(config::get_policy_path())),
+            trace,
+            last_trace_flush: Instant::now(),
+            predictor: None,
+            engine: EngineHandle::spawn(),
             mixer: Mixer::new(),
             
             chat_history: vec![
@@ -78,11 +207,21 @@ impl Cartesian {
             vision_status: "NO SIGNAL".to_string(),
             brain_state: "INITIALIZING...".to_string(),
             current_context: AppCategory::System,
-            debug_override: false,
             cpu_usage: 0.0,
             free_ram: 0.0,
-            unknown_count: 0,
-        }, Task::none())
+            pressure: None,
+            power: None,
+            temperature_c: None,
+            vram_probe,
+            vram: None,
+            unknown_apps: Vec::new(),
+            hyprland,
+            focus: None,
+            ceiling: None,
+            categories,
+            effects: Effects::default(),
+            own_usage: SelfUsage::default(),
+        }, Task::perform(train_predictor(), Message::PredictorTrained))
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
@@ -99,14 +238,50 @@ impl Cartesian {
                     timestamp: "Now".to_string(),
                 });
                 self.input_value.clear();
+
+                // --- MEMORY COMMANDS (handled by the Hippocampus worker) ---
+                if let Some(path) = user_msg.strip_prefix("/index ") {
+                    self.memory.index(PathBuf::from(path.trim()));
+                    self.chat_history.push(ChatMessage {
+                        sender: "SYSTEM".to_string(),
+                        content: format!("Indexing {} in the background.", path.trim()),
+                        timestamp: "Now".to_string(),
+                    });
+                    return Task::none();
+                }
+                if let Some(query) = user_msg.strip_prefix("/recall ") {
+                    return Task::perform(
+                        self.memory.clone().retrieve_context_async(query.trim().to_string()),
+                        Message::Recalled,
+                    );
+                }
                 
                 // --- MULTIMODAL INFERENCE CALL ---
                 // 1. Grab visual context
-                let visual_context = self.eye.observe()
+                let brakes = resources::controller();
+                let visual_context = (!brakes.is_paused(Work::Capture)).then(|| self.eye.observe()).flatten()
                     .and_then(|cortex| cortex.to_dynamic_image());
                 
                 // 2. Infer Action
-                if let Some(action) = self.engine.infer_action(&user_msg, visual_context.as_ref()) {
+                if let Some(by) = brakes.paused_by(Work::Generation).first() {
+                    self.chat_history.push(ChatMessage {
+                        sender: "SYSTEM".to_string(),
+                        content: format!("Generation is paused ({}).", by.label()),
+                        timestamp: "Now".to_string(),
+                    });
+                } else {
+                    return Task::perform(self.engine.clone().infer_action(user_msg, visual_context), Message::Replied);
+                }
+            }
+            Message::Quit => {
+                self.memory.shutdown();
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.flush();
+                }
+                return iced::exit();
+            }
+            Message::Replied(action) => {
+                if let Some(action) = action {
                     self.chat_history.push(ChatMessage {
                         sender: "CARTESIAN".to_string(),
                         content: action.user_message,
@@ -130,50 +305,219 @@ impl Cartesian {
                 let (cpu, ram) = self.monitor.get_vitals();
                 self.cpu_usage = cpu;
                 self.free_ram = ram;
+                self.pressure = self.monitor.get_pressure();
+                self.power = self.monitor.get_power();
+                self.temperature_c = self.monitor.get_temperature();
+                self.vram = self.vram_probe.as_mut().and_then(|probe| probe.read());
 
-                let (context, unknowns) = self.monitor.get_system_context();
-                self.current_context = context;
-                self.unknown_count = unknowns.len();
-                
-                if self.debug_override {
-                    self.current_context = AppCategory::Game;
+                self.focus = self.hyprland.as_ref().and_then(|h| h.focused());
+                let hints = ContextHints {
+                    focused_pid: self.focus.as_ref().and_then(|f| f.pid),
+                    focused_fullscreen: self.focus.as_ref().is_some_and(|f| f.fullscreen),
+                    gpu_bytes: self.vram_probe.as_mut().map(|probe| probe.process_usage()).unwrap_or_default(),
+                };
+                let report = self.monitor.get_system_context(&hints);
+                // No entry in a probe's list means no GPU memory, not unknown
+                let own_gpu = self.vram_probe.as_ref()
+                    .map(|_| hints.gpu_bytes.get(&std::process::id()).copied().unwrap_or(0));
+                self.own_usage = self.monitor.get_self_usage(own_gpu);
+                self.own_usage.attribute(Subsystem::Inference, self.engine.resident());
+                self.own_usage.attribute(Subsystem::Embedding, self.memory.embedder_resident());
+                self.current_context = report.dominant;
+                self.status = match report.active.as_slice() {
+                    [] => "SYSTEM IDLE".to_string(),
+                    active => active.iter().map(|(c, _)| c.name().to_uppercase()).collect::<Vec<_>>().join(" + "),
+                };
+                let active_contexts: Vec<AppCategory> = report.active.iter().map(|(c, _)| *c).collect();
+                let unknown_names = report.unknown_apps.iter().map(|app| app.name.clone()).collect();
+                self.unknown_apps = report.unknown_apps;
+                self.effects = self.categories.effects(&active_contexts);
+                // The stricter of the registry's and the categories' ceilings
+                let category_ceiling = self.effects.max_state.map(|(category, max)| (category.name().to_string(), max));
+                self.ceiling = match (report.ceiling, category_ceiling) {
+                    (Some(app), Some(category)) => Some(if category.1.rank() < app.1.rank() { category } else { app }),
+                    (app, category) => app.or(category),
+                };
+                self.mixer.set_profile(self.effects.audio);
+
+                // Hot-reload the policy file; a broken edit keeps the last good policy
+                match self.policy_watcher.poll() {
+                    Some(Ok(policy)) => self.governor.set_policy(policy),
+                    Some(Err(e)) => eprintln!("Governor: Policy rejected, keeping previous: {}", e),
+                    None => {}
                 }
 
-                let is_gaming = self.current_context == AppCategory::Game;
-                let vram_pressure = self.current_context == AppCategory::Production;
+                let now = chrono::Local::now();
+                let hour = now.hour() as u8;
+                let signals: Vec<String> = self.monitor.active_launchers().iter()
+                    .map(|l| format!("launcher:{}", l))
+                    .chain(self.focus.as_ref().and_then(|f| f.workspace.as_ref()).map(|ws| format!("workspace:{}", ws)))
+                    .collect();
 
-                let state = self.governor.decide_state(self.free_ram, is_gaming, vram_pressure);
-                self.engine.apply_state(&state);
+                let inputs = GovernorInputs {
+                    free_ram_gb: self.free_ram,
+                    cpu_percent: self.cpu_usage,
+                    pressure: self.pressure,
+                    free_vram_gb: self.vram.map(|v| v.free_gb()),
+                    // Whether God Mode, as the policy defines it, would fit
+                    vram_headroom_gb: self.vram.zip(vram::profile_footprint_gb(&self.governor.profile_for(GovernorState::GodMode)))
+                        .map(|(v, god)| vram::headroom_gb(&v, self.own_usage.resident(Subsystem::Inference).vram_gb, god)),
+                    context: self.current_context,
+                    active_contexts,
+                    game_likelihood: self.predictor.as_ref().and_then(|p| p.likelihood(hour, &signals)),
+                    power: self.power.map(|p| p.source),
+                    battery_percent: self.power.and_then(|p| p.charge_percent),
+                    temperature_c: self.temperature_c,
+                    hour,
+                    weekday: Some(now.weekday().into()),
+                    ceiling: self.ceiling.as_ref().map(|(_, state)| *state),
+                    own_cpu_percent: Some(self.own_usage.cpu_percent),
+                    own_ram_gb: self.own_usage.rss_gb(),
+                    own_vram_gb: self.own_usage.gpu_gb(),
+                    own_inference_vram_gb: Some(self.own_usage.resident(Subsystem::Inference).vram_gb),
+                    own_embedding_ram_gb: Some(self.own_usage.resident(Subsystem::Embedding).ram_gb),
+                };
+                let state = self.governor.decide_state(&inputs);
+                let profile = self.governor.profile();
+                self.engine.apply_profile(&profile);
+                self.memory.set_background(profile.budget(BackgroundJob::Consolidation));
+                self.apply_brakes(&profile, state);
+                if let Some(confinement) = cgroup::get() {
+                    if let Err(e) = confinement.apply(&profile) {
+                        eprintln!("Confinement: {}", e);
+                    }
+                }
+                self.record_tick(inputs, unknown_names, signals, state);
 
-                self.brain_state = format!(
-                    "{} [{}]", 
-                    match state {
-                        GovernorState::GodMode => "GOD MODE",
-                        GovernorState::Conscientious => "CONSCIENTIOUS",
-                        GovernorState::SidekickMode => "SIDEKICK",
-                        GovernorState::PotatoMode => "POTATO",
-                    },
-                    self.engine.current_model()
-                );
+                self.brain_state = format!("{} [{}]", state.label(), self.engine.current_model());
 
+                let mut task = Task::none();
+                // A paused Witness doesn't even look (a game, a call, the admin)
+                if let Some(by) = resources::controller().paused_by(Work::Capture).first() {
+                    self.vision_status = format!("CAPTURE PAUSED ({})", by.label());
+                    return task;
+                }
                 match self.eye.observe() {
-                    Some(frame) => self.vision_status = format!("INPUT [{}x{}]", frame.width, frame.height),
+                    Some(frame) => {
+                        self.vision_status = format!("INPUT [{}x{}]", frame.width, frame.height);
+                        if self.sampler.should_sample(&frame, profile.budget(BackgroundJob::Witness)) {
+                            task = self.remember_frame(frame);
+                        }
+                    }
                     None => if self.eye.observe().is_none() { self.vision_status = "NO SIGNAL".to_string() }
                 }
+                return task;
+            }
+            Message::Recalled(blocks) => {
+                self.chat_history.push(ChatMessage {
+                    sender: "HIPPOCAMPUS".to_string(),
+                    content: if blocks.is_empty() { "Nothing recalled.".to_string() } else { blocks.join("\n---\n") },
+                    timestamp: "Now".to_string(),
+                });
+            }
+            Message::PredictorTrained(predictor) => {
+                if let Some(p) = &predictor {
+                    println!("Governor: Game predictor trained ({} features).", p.feature_count());
+                }
+                self.predictor = predictor;
+            }
+            Message::FrameObserved(observation) => {
+                if let Some(obs) = observation.filter(|o| !o.description.is_empty()) {
+                    self.memory.remember_visual(obs);
+                }
+            }
+            Message::Hold(state) => {
+                self.governor.hold(state, config::GOVERNOR_HOLD_STEP);
             }
-            Message::ToggleOverride => {
-                self.debug_override = !self.debug_override;
+            Message::ReleaseHold => {
+                self.governor.release_hold();
+            }
+            Message::Learn(name, category) => {
+                match self.monitor.learn(&name, category) {
+                    Ok(()) => println!("Lobotomy: Learned {} as {}", name, category.name()),
+                    Err(e) => eprintln!("Lobotomy: Registry not saved: {}", e),
+                }
+                self.unknown_apps.retain(|app| app.name != name);
             }
         }
         Task::none()
     }
 
+    /// The Governor's side of the ResourceController: what the profile and the
+    /// active categories pause, and how hard the worker threads may run.
+    fn apply_brakes(&self, profile: &EngineProfile, state: GovernorState) {
+        let brakes = resources::controller();
+        let potato = state == GovernorState::PotatoMode;
+        for (work, paused) in [
+            (Work::Capture, !self.effects.witness || profile.budget(BackgroundJob::Witness) == BackgroundBudget::Paused),
+            (Work::Indexing, !self.effects.indexing || profile.budget(BackgroundJob::Consolidation) == BackgroundBudget::Paused),
+            (Work::Consolidation, potato),
+            (Work::Generation, potato),
+        ] {
+            if brakes.set_paused(work, Requester::Governor, paused) {
+                println!("Resources: {} {} by the Governor", work.name(), if paused { "paused" } else { "resumed" });
+            }
+        }
+        if let Err(e) = brakes.set_priority(Work::Indexing, Priority::for_budget(profile.budget(BackgroundJob::Consolidation))) {
+            eprintln!("Resources: {}", e);
+        }
+    }
+
+    /// Append the tick to the Governor trace (replay with `cartesian-core replay`).
+    fn record_tick(&mut self, inputs: GovernorInputs, unknown_apps: Vec<String>, signals: Vec<String>, state: GovernorState) {
+        let Some(trace) = self.trace.as_mut() else { return; };
+        let record = TraceRecord {
+            t_ms: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+            inputs,
+            unknown_apps,
+            signals,
+            override_active: self.governor.hold_remaining().is_some(),
+            state,
+            rule: self.governor.reason().to_string(),
+        };
+        if let Err(e) = trace.record(&record) {
+            eprintln!("Telemetry: Governor trace disabled: {}", e);
+            self.trace = None;
+            return;
+        }
+        if self.last_trace_flush.elapsed() >= config::TRACE_FLUSH_INTERVAL {
+            trace.flush();
+            self.last_trace_flush = Instant::now();
+        }
+    }
+
+    /// OCR and thumbnail off the UI thread. Frames aren't captioned: the
+    /// Engine doesn't load the vision projector, so the text on screen is it.
+    fn remember_frame(&mut self, frame: VisualCortex) -> Task<Message> {
+        let Some(image) = frame.to_dynamic_image() else { return Task::none(); };
+        let captured_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let _confined = cgroup::enter(Group::Vision);
+                    let _span = accounting::enter(Subsystem::Vision);
+                    let ocr = witness::read_text(&image).map(|text| format!("Text on screen: {}", text));
+                    VisualObservation {
+                        captured_at,
+                        description: ocr.unwrap_or_default(),
+                        thumbnail: frame.thumbnail_png(config::VISUAL_THUMBNAIL_SIZE),
+                    }
+                }).await.ok()
+            },
+            Message::FrameObserved,
+        )
+    }
+
     fn view(&self) -> Element<'_, Message> {
         ui::dashboard::view(self)
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        time::every(config::TICK_RATE).map(|_| Message::Tick)
+        Subscription::batch([
+            time::every(config::TICK_RATE).map(|_| Message::Tick),
+            iced::window::close_requests().map(|_| Message::Quit),
+        ])
     }
 
     fn theme(&self) -> Theme {
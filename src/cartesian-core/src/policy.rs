@@ -0,0 +1,711 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use crate::inference::{BackgroundJob, EngineProfile, GovernorState, Hysteresis};
+use crate::lobotomy::AppCategory;
+use crate::pressure::PsiSnapshot;
+
+pub const POLICY_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Mon, Tue, Wed, Thu, Fri, Sat, Sun,
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(day: chrono::Weekday) -> Self {
+        match day {
+            chrono::Weekday::Mon => Weekday::Mon,
+            chrono::Weekday::Tue => Weekday::Tue,
+            chrono::Weekday::Wed => Weekday::Wed,
+            chrono::Weekday::Thu => Weekday::Thu,
+            chrono::Weekday::Fri => Weekday::Fri,
+            chrono::Weekday::Sat => Weekday::Sat,
+            chrono::Weekday::Sun => Weekday::Sun,
+        }
+    }
+}
+
+/// Everything the Governor looks at on a tick. Optional fields are
+/// inputs the host may not provide (no GPU probe, desktop without battery...).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernorInputs {
+    pub free_ram_gb: f32,
+    pub cpu_percent: f32,
+    pub pressure: Option<PsiSnapshot>,
+    pub free_vram_gb: Option<f32>,
+    pub vram_headroom_gb: Option<f32>, // Free VRAM once the GodMode model is resident
+    pub context: AppCategory, // Dominant
+    #[serde(default)]
+    pub active_contexts: Vec<AppCategory>, // Every busy category, strongest first
+    #[serde(default)]
+    pub game_likelihood: Option<f32>, // Predicted chance a game starts soon (0-1)
+    pub power: Option<PowerSource>,
+    pub battery_percent: Option<f32>,
+    pub temperature_c: Option<f32>,
+    pub hour: u8, // Local time, 0-23
+    #[serde(default)]
+    pub weekday: Option<Weekday>, // Local time; missing in older traces
+    #[serde(default)]
+    pub ceiling: Option<GovernorState>, // Strictest registry or category hint
+    // cartesian-core's own footprint
+    #[serde(default)]
+    pub own_cpu_percent: Option<f32>,
+    #[serde(default)]
+    pub own_ram_gb: Option<f32>,
+    #[serde(default)]
+    pub own_vram_gb: Option<f32>,
+    // The part of it held by the loaded models
+    #[serde(default)]
+    pub own_inference_vram_gb: Option<f32>,
+    #[serde(default)]
+    pub own_embedding_ram_gb: Option<f32>,
+}
+
+impl GovernorInputs {
+    /// Older traces only recorded the dominant context.
+    pub fn active(&self) -> &[AppCategory] {
+        if self.active_contexts.is_empty() { std::slice::from_ref(&self.context) } else { &self.active_contexts }
+    }
+
+    /// A quiet machine at noon with no optional readings, for tests to adjust.
+    #[cfg(test)]
+    pub fn idle() -> Self {
+        Self {
+            free_ram_gb: 8.0,
+            cpu_percent: 10.0,
+            pressure: None,
+            free_vram_gb: None,
+            vram_headroom_gb: None,
+            context: AppCategory::System,
+            active_contexts: Vec::new(),
+            game_likelihood: None,
+            power: None,
+            battery_percent: None,
+            temperature_c: None,
+            hour: 12,
+            weekday: None,
+            ceiling: None,
+            own_cpu_percent: None,
+            own_ram_gb: None,
+            own_vram_gb: None,
+            own_inference_vram_gb: None,
+            own_embedding_ram_gb: None,
+        }
+    }
+}
+
+/// All present conditions must hold. A condition on an input the host
+/// doesn't provide never matches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Conditions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_ram_below_gb: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_ram_above_gb: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_above: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_below: Option<f32>,
+    // Stall thresholds: percent of time some task waited, averaged over 60 s
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_stall_above: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_stall_above: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io_stall_above: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_vram_below_gb: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_vram_above_gb: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vram_headroom_below_gb: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vram_known: Option<bool>, // Whether a VRAM probe is reporting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<Vec<AppCategory>>, // Dominant context is any of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<Vec<AppCategory>>, // Any of these is among the active contexts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_likely_above: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery_below_percent: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_above_c: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hours: Option<[u8; 2]>, // [start, end) local hours; wraps past midnight
+    // Our own share: lets a rule tell "the machine is busy" from "we are the load"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub own_cpu_above: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub own_ram_above_gb: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub own_vram_above_gb: Option<f32>,
+}
+
+impl Conditions {
+    pub fn matches(&self, i: &GovernorInputs) -> bool {
+        fn below(limit: Option<f32>, value: Option<f32>) -> bool {
+            limit.is_none_or(|l| value.is_some_and(|v| v < l))
+        }
+        fn above(limit: Option<f32>, value: Option<f32>) -> bool {
+            limit.is_none_or(|l| value.is_some_and(|v| v > l))
+        }
+
+        below(self.free_ram_below_gb, Some(i.free_ram_gb))
+            && above(self.free_ram_above_gb, Some(i.free_ram_gb))
+            && above(self.cpu_above, Some(i.cpu_percent))
+            && below(self.cpu_below, Some(i.cpu_percent))
+            && above(self.cpu_stall_above, i.pressure.map(|p| p.cpu.some.avg60))
+            && above(self.memory_stall_above, i.pressure.map(|p| p.memory.some.avg60))
+            && above(self.io_stall_above, i.pressure.map(|p| p.io.some.avg60))
+            && below(self.free_vram_below_gb, i.free_vram_gb)
+            && above(self.free_vram_above_gb, i.free_vram_gb)
+            && below(self.vram_headroom_below_gb, i.vram_headroom_gb)
+            && self.vram_known.is_none_or(|known| known == i.free_vram_gb.is_some())
+            && above(self.temperature_above_c, i.temperature_c)
+            && below(self.battery_below_percent, i.battery_percent)
+            && self.context.as_ref().is_none_or(|c| c.contains(&i.context))
+            && self.active.as_ref().is_none_or(|c| i.active().iter().any(|a| c.contains(a)))
+            && above(self.game_likely_above, i.game_likelihood)
+            && self.power.is_none_or(|p| i.power == Some(p))
+            && self.hours.is_none_or(|[start, end]| in_hours(i.hour, start, end))
+            && above(self.own_cpu_above, i.own_cpu_percent)
+            && above(self.own_ram_above_gb, i.own_ram_gb)
+            && above(self.own_vram_above_gb, i.own_vram_gb)
+    }
+
+    fn is_unconditional(&self) -> bool {
+        self.free_ram_below_gb.is_none() && self.free_ram_above_gb.is_none()
+            && self.cpu_above.is_none() && self.cpu_below.is_none()
+            && self.cpu_stall_above.is_none() && self.memory_stall_above.is_none() && self.io_stall_above.is_none()
+            && self.free_vram_below_gb.is_none() && self.free_vram_above_gb.is_none()
+            && self.vram_headroom_below_gb.is_none() && self.vram_known.is_none()
+            && self.context.is_none() && self.active.is_none() && self.game_likely_above.is_none() && self.power.is_none() && self.battery_below_percent.is_none()
+            && self.temperature_above_c.is_none() && self.hours.is_none()
+            && self.own_cpu_above.is_none() && self.own_ram_above_gb.is_none() && self.own_vram_above_gb.is_none()
+    }
+}
+
+fn in_hours(hour: u8, start: u8, end: u8) -> bool {
+    if start <= end { hour >= start && hour < end } else { hour >= start || hour < end }
+}
+
+/// A time window that pins the Governor to `state`, whatever the rules say,
+/// except that the rules can still force PotatoMode (out-of-memory guard).
+/// Jobs listed in `exempt` keep running at a full budget meanwhile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schedule {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<Vec<Weekday>>, // Any of; None = every day
+    pub hours: [u8; 2], // [start, end) local hours; wraps past midnight
+    pub state: GovernorState,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exempt: Vec<BackgroundJob>,
+}
+
+impl Schedule {
+    /// A window that wraps past midnight belongs to the day it started on.
+    pub fn is_active(&self, weekday: Option<Weekday>, hour: u8) -> bool {
+        let [start, end] = self.hours;
+        if !in_hours(hour, start, end) {
+            return false;
+        }
+        let Some(days) = &self.days else { return true; };
+        let Some(today) = weekday else { return false; };
+        let day = if start > end && hour < end { previous_day(today) } else { today };
+        days.contains(&day)
+    }
+}
+
+fn previous_day(day: Weekday) -> Weekday {
+    match day {
+        Weekday::Mon => Weekday::Sun,
+        Weekday::Tue => Weekday::Mon,
+        Weekday::Wed => Weekday::Tue,
+        Weekday::Thu => Weekday::Wed,
+        Weekday::Fri => Weekday::Thu,
+        Weekday::Sat => Weekday::Fri,
+        Weekday::Sun => Weekday::Sat,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    pub name: String,
+    #[serde(default)]
+    pub when: Conditions,
+    pub state: GovernorState,
+}
+
+/// THE POLICY
+/// Ordered rules mapping system conditions to a GovernorState. First match wins;
+/// `default_state` applies when nothing matches. The first active schedule
+/// overrides the outcome. Loaded from
+/// `$XDG_CONFIG_HOME/cartesian/governor_policy.json` and hot-reloaded on change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GovernorPolicy {
+    pub version: u32,
+    pub rules: Vec<PolicyRule>,
+    pub default_state: GovernorState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hysteresis: Option<Hysteresis>,
+    // Per-state Engine settings; states left out use EngineProfile::builtin
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<GovernorState, EngineProfile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<Schedule>,
+}
+
+/// What the policy picked on a tick and why.
+#[derive(Debug, Clone)]
+pub struct Decision<'a> {
+    pub state: GovernorState,
+    pub rule: &'a str,
+    pub schedule: Option<&'a Schedule>,
+}
+
+impl Default for GovernorPolicy {
+    /// The original hard-coded heuristics.
+    fn default() -> Self {
+        Self {
+            version: POLICY_VERSION,
+            rules: vec![
+                PolicyRule {
+                    name: "low-memory".to_string(),
+                    when: Conditions { free_ram_below_gb: Some(2.0), ..Default::default() },
+                    state: GovernorState::PotatoMode,
+                },
+                PolicyRule {
+                    name: "gaming".to_string(),
+                    // Any busy game, even behind a focused editor
+                    when: Conditions { active: Some(vec![AppCategory::Game]), ..Default::default() },
+                    state: GovernorState::SidekickMode,
+                },
+                // Laptops: keep the GPU cool and the battery alive
+                PolicyRule {
+                    name: "on-battery".to_string(),
+                    when: Conditions { power: Some(PowerSource::Battery), ..Default::default() },
+                    state: GovernorState::SidekickMode,
+                },
+                PolicyRule {
+                    name: "overheating".to_string(),
+                    when: Conditions { temperature_above_c: Some(85.0), ..Default::default() },
+                    state: GovernorState::SidekickMode,
+                },
+                // Sustained stalls mean the user is already waiting on us
+                PolicyRule {
+                    name: "memory-stall".to_string(),
+                    when: Conditions { memory_stall_above: Some(10.0), ..Default::default() },
+                    state: GovernorState::SidekickMode,
+                },
+                PolicyRule {
+                    name: "io-stall".to_string(),
+                    when: Conditions { io_stall_above: Some(25.0), ..Default::default() },
+                    state: GovernorState::Conscientious,
+                },
+                // Pre-shrink before the game allocates VRAM, so the launch doesn't stutter
+                PolicyRule {
+                    name: "game-likely".to_string(),
+                    when: Conditions { game_likely_above: Some(0.6), ..Default::default() },
+                    state: GovernorState::Conscientious,
+                },
+                PolicyRule {
+                    name: "vram-pressure".to_string(),
+                    when: Conditions { vram_headroom_below_gb: Some(0.0), ..Default::default() },
+                    state: GovernorState::Conscientious,
+                },
+                // Without a probe, a running Production app is the best VRAM proxy we have
+                PolicyRule {
+                    name: "production-no-probe".to_string(),
+                    when: Conditions {
+                        context: Some(vec![AppCategory::Production]),
+                        vram_known: Some(false),
+                        ..Default::default()
+                    },
+                    state: GovernorState::Conscientious,
+                },
+            ],
+            default_state: GovernorState::GodMode,
+            hysteresis: None,
+            profiles: [
+                GovernorState::GodMode, GovernorState::Conscientious,
+                GovernorState::SidekickMode, GovernorState::PotatoMode,
+            ].into_iter().map(|s| (s, EngineProfile::builtin(s))).collect(),
+            schedules: Vec::new(),
+        }
+    }
+}
+
+impl GovernorPolicy {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| format!("{:?}: {}", path, e))?;
+        let policy: Self = serde_json::from_str(&data).map_err(|e| format!("{:?}: {}", path, e))?;
+        policy.validate().map_err(|errors| format!("{:?}: {}", path, errors.join("; ")))?;
+        Ok(policy)
+    }
+
+    pub fn profile(&self, state: GovernorState) -> EngineProfile {
+        self.profiles.get(&state).cloned().unwrap_or_else(|| EngineProfile::builtin(state))
+    }
+
+    pub fn evaluate(&self, inputs: &GovernorInputs) -> Decision<'_> {
+        let (state, rule) = self.rules.iter()
+            .find(|rule| rule.when.matches(inputs))
+            .map(|rule| (rule.state, rule.name.as_str()))
+            .unwrap_or((self.default_state, "default"));
+
+        let mut decision = Decision { state, rule, schedule: None };
+        if state != GovernorState::PotatoMode {
+            if let Some(schedule) = self.schedules.iter().find(|s| s.is_active(inputs.weekday, inputs.hour)) {
+                decision = Decision { state: schedule.state, rule: &schedule.name, schedule: Some(schedule) };
+            }
+        }
+        // A registry or category hint caps whatever the rules or a schedule chose
+        match inputs.ceiling {
+            Some(ceiling) if ceiling.rank() < decision.state.rank() => Decision { state: ceiling, rule: "ceiling", schedule: None },
+            _ => decision,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.version != POLICY_VERSION {
+            errors.push(format!("unsupported version {} (expected {})", self.version, POLICY_VERSION));
+        }
+
+        let mut names = HashSet::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            let label = if rule.name.is_empty() { format!("rule #{}", i + 1) } else { format!("rule '{}'", rule.name) };
+            let w = &rule.when;
+
+            if rule.name.is_empty() {
+                errors.push(format!("{}: missing name", label));
+            } else if !names.insert(rule.name.as_str()) {
+                errors.push(format!("{}: duplicate name", label));
+            }
+
+            let thresholds = [
+                w.free_ram_below_gb, w.free_ram_above_gb, w.cpu_above, w.cpu_below,
+                w.free_vram_below_gb, w.free_vram_above_gb, w.temperature_above_c,
+                w.cpu_stall_above, w.memory_stall_above, w.io_stall_above, w.battery_below_percent,
+                w.own_cpu_above, w.own_ram_above_gb, w.own_vram_above_gb,
+            ];
+            if thresholds.iter().flatten().any(|v| !v.is_finite() || *v < 0.0) {
+                errors.push(format!("{}: thresholds must be finite and non-negative", label));
+            }
+            let stalls = [w.cpu_stall_above, w.memory_stall_above, w.io_stall_above];
+            if stalls.iter().flatten().any(|v| *v > 100.0) {
+                errors.push(format!("{}: stall thresholds are percentages (0-100)", label));
+            }
+            // Headroom goes negative when the model doesn't fit
+            if w.vram_headroom_below_gb.is_some_and(|v| !v.is_finite()) {
+                errors.push(format!("{}: vram_headroom_below_gb must be finite", label));
+            }
+            if let (Some(b), Some(a)) = (w.free_ram_below_gb, w.free_ram_above_gb) {
+                if b <= a { errors.push(format!("{}: free_ram_below_gb <= free_ram_above_gb never matches", label)); }
+            }
+            if let (Some(b), Some(a)) = (w.cpu_below, w.cpu_above) {
+                if b <= a { errors.push(format!("{}: cpu_below <= cpu_above never matches", label)); }
+            }
+            if let (Some(b), Some(a)) = (w.free_vram_below_gb, w.free_vram_above_gb) {
+                if b <= a { errors.push(format!("{}: free_vram_below_gb <= free_vram_above_gb never matches", label)); }
+            }
+            if let Some([start, end]) = w.hours {
+                if start > 23 || end > 24 || start == end {
+                    errors.push(format!("{}: hours must be [start, end) within 0-24", label));
+                }
+            }
+            if w.game_likely_above.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+                errors.push(format!("{}: game_likely_above is a probability (0-1)", label));
+            }
+            if w.context.as_ref().is_some_and(|c| c.is_empty()) || w.active.as_ref().is_some_and(|c| c.is_empty()) {
+                errors.push(format!("{}: empty context list never matches", label));
+            }
+            // Names categories.json doesn't define read as Unknown, which is never a context
+            if w.context.iter().chain(&w.active).flatten().any(|c| *c == AppCategory::Unknown) {
+                errors.push(format!("{}: context names a category that isn't defined", label));
+            }
+            if w.is_unconditional() && i + 1 < self.rules.len() {
+                errors.push(format!("{}: matches everything, later rules are unreachable", label));
+            }
+        }
+
+        for (i, schedule) in self.schedules.iter().enumerate() {
+            let label = if schedule.name.is_empty() { format!("schedule #{}", i + 1) } else { format!("schedule '{}'", schedule.name) };
+            if schedule.name.is_empty() {
+                errors.push(format!("{}: missing name", label));
+            } else if !names.insert(schedule.name.as_str()) {
+                errors.push(format!("{}: name already used by a rule or schedule", label));
+            }
+            let [start, end] = schedule.hours;
+            if start > 23 || end > 24 || start == end {
+                errors.push(format!("{}: hours must be [start, end) within 0-24", label));
+            }
+            if schedule.days.as_ref().is_some_and(|d| d.is_empty()) {
+                errors.push(format!("{}: empty days list never matches", label));
+            }
+        }
+
+        for (state, profile) in &self.profiles {
+            if profile.model.as_ref().is_some_and(|m| m.is_empty()) {
+                errors.push(format!("profile {:?}: empty model name (use null to unload)", state));
+            }
+            if profile.model.is_some() && profile.context_length == 0 {
+                errors.push(format!("profile {:?}: context_length must be at least 1", state));
+            }
+            if profile.batch_size == 0 {
+                errors.push(format!("profile {:?}: batch_size must be at least 1", state));
+            }
+            if !(1..=10000).contains(&profile.cpu_weight) {
+                errors.push(format!("profile {:?}: cpu_weight must be 1-10000", state));
+            }
+            if profile.cpu_max.is_some_and(|v| !v.is_finite() || v <= 0.0)
+                || profile.memory_high_gb.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+                errors.push(format!("profile {:?}: cpu_max and memory_high_gb must be positive", state));
+            }
+        }
+
+        if let Some(hysteresis) = &self.hysteresis {
+            if [hysteresis.upgrade_secs, hysteresis.downgrade_secs].iter().any(|v| !v.is_finite() || *v < 0.0) {
+                errors.push("hysteresis: upgrade_secs and downgrade_secs must be finite and non-negative".to_string());
+            }
+            for (i, rule) in hysteresis.rules.iter().enumerate() {
+                let label = match rule.from {
+                    Some(from) => format!("hysteresis rule #{} ({:?} -> {:?})", i + 1, from, rule.to),
+                    None => format!("hysteresis rule #{} (-> {:?})", i + 1, rule.to),
+                };
+                if !rule.secs.is_finite() || rule.secs < 0.0 {
+                    errors.push(format!("{}: secs must be finite and non-negative", label));
+                }
+                match rule.from {
+                    Some(from) if from == rule.to => errors.push(format!("{}: a state never moves to itself", label)),
+                    // Without `from` a rule only applies to downgrades
+                    None if rule.to == GovernorState::GodMode => errors.push(format!("{}: nothing downgrades to GodMode", label)),
+                    _ => {}
+                }
+                // First match wins: an earlier rule for the same target may cover this one
+                let shadowed = hysteresis.rules[..i].iter().any(|earlier| earlier.to == rule.to && match (earlier.from, rule.from) {
+                    (None, Some(from)) => rule.to.rank() < from.rank(),
+                    (earlier, from) => earlier == from,
+                });
+                if shadowed {
+                    errors.push(format!("{}: an earlier rule already covers it", label));
+                }
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Hot reload by polling the file's mtime (cheap enough to do every tick).
+pub struct PolicyWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+}
+
+impl PolicyWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, last_modified: None }
+    }
+
+    /// Some(..) when the file appeared or changed since the last call.
+    /// A deleted file falls back to the built-in policy.
+    pub fn poll(&mut self) -> Option<Result<GovernorPolicy, String>> {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified == self.last_modified {
+            return None;
+        }
+        self.last_modified = modified;
+
+        match modified {
+            Some(_) => Some(GovernorPolicy::load(&self.path)),
+            None => Some(Ok(GovernorPolicy::default())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::DwellRule;
+
+    fn inputs(free_ram_gb: f32, weekday: Weekday, hour: u8) -> GovernorInputs {
+        GovernorInputs { free_ram_gb, weekday: Some(weekday), hour, ..GovernorInputs::idle() }
+    }
+
+    fn overnight() -> Schedule {
+        Schedule {
+            name: "overnight".to_string(),
+            days: Some(vec![Weekday::Fri]),
+            hours: [23, 7],
+            state: GovernorState::SidekickMode,
+            exempt: vec![BackgroundJob::Consolidation],
+        }
+    }
+
+    #[test]
+    fn schedule_window_wrapping_midnight_belongs_to_its_start_day() {
+        let schedule = overnight();
+        assert!(schedule.is_active(Some(Weekday::Fri), 23));
+        assert!(schedule.is_active(Some(Weekday::Sat), 3));
+        assert!(!schedule.is_active(Some(Weekday::Fri), 3));
+        assert!(!schedule.is_active(Some(Weekday::Sat), 7));
+        assert!(!schedule.is_active(None, 23), "day-restricted schedules need a weekday");
+    }
+
+    #[test]
+    fn schedules_override_rules_but_not_potato() {
+        let policy = GovernorPolicy { schedules: vec![overnight()], ..Default::default() };
+
+        let decision = policy.evaluate(&inputs(8.0, Weekday::Sat, 2));
+        assert_eq!((decision.state, decision.rule), (GovernorState::SidekickMode, "overnight"));
+        assert!(decision.schedule.is_some());
+
+        let decision = policy.evaluate(&inputs(1.0, Weekday::Sat, 2));
+        assert_eq!((decision.state, decision.rule), (GovernorState::PotatoMode, "low-memory"));
+
+        let decision = policy.evaluate(&inputs(8.0, Weekday::Sat, 12));
+        assert_eq!((decision.state, decision.rule), (GovernorState::GodMode, "default"));
+    }
+
+    #[test]
+    fn registry_ceiling_caps_but_never_raises() {
+        let policy = GovernorPolicy { schedules: vec![overnight()], ..Default::default() };
+        let capped = |free_ram_gb, hour| GovernorInputs {
+            ceiling: Some(GovernorState::Conscientious),
+            ..inputs(free_ram_gb, Weekday::Sat, hour)
+        };
+
+        let decision = policy.evaluate(&capped(8.0, 12));
+        assert_eq!((decision.state, decision.rule), (GovernorState::Conscientious, "ceiling"));
+        // Already below the ceiling
+        assert_eq!(policy.evaluate(&capped(8.0, 2)).state, GovernorState::SidekickMode);
+        assert_eq!(policy.evaluate(&capped(1.0, 12)).state, GovernorState::PotatoMode);
+    }
+
+    #[test]
+    fn schedule_names_must_not_clash_with_rules() {
+        let mut schedule = overnight();
+        schedule.name = "gaming".to_string();
+        let policy = GovernorPolicy { schedules: vec![schedule], ..Default::default() };
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let policy = GovernorPolicy::default();
+        let decide = |i: &GovernorInputs| { let d = policy.evaluate(i); (d.state, d.rule.to_string()) };
+        let calm = inputs(8.0, Weekday::Mon, 12);
+        assert_eq!(decide(&calm), (GovernorState::GodMode, "default".to_string()));
+
+        // A game running behind a focused editor still counts
+        let gaming = GovernorInputs {
+            context: AppCategory::Development,
+            active_contexts: vec![AppCategory::Development, AppCategory::Game],
+            ..calm.clone()
+        };
+        assert_eq!(decide(&gaming), (GovernorState::SidekickMode, "gaming".to_string()));
+        // Low memory outranks the game
+        assert_eq!(decide(&GovernorInputs { free_ram_gb: 1.5, ..gaming.clone() }), (GovernorState::PotatoMode, "low-memory".to_string()));
+
+        // Production only stands in for VRAM pressure without a probe
+        let production = GovernorInputs { context: AppCategory::Production, ..calm.clone() };
+        assert_eq!(decide(&production), (GovernorState::Conscientious, "production-no-probe".to_string()));
+        let probed = GovernorInputs { free_vram_gb: Some(6.0), vram_headroom_gb: Some(2.0), ..production };
+        assert_eq!(decide(&probed), (GovernorState::GodMode, "default".to_string()));
+        let tight = GovernorInputs { vram_headroom_gb: Some(-0.5), ..probed };
+        assert_eq!(decide(&tight), (GovernorState::Conscientious, "vram-pressure".to_string()));
+
+        // Missing readings never satisfy a threshold
+        let hot = GovernorInputs { temperature_c: Some(90.0), ..calm.clone() };
+        assert_eq!(decide(&hot), (GovernorState::SidekickMode, "overheating".to_string()));
+        assert_eq!(decide(&GovernorInputs { temperature_c: None, ..hot }).1, "default");
+    }
+
+    #[test]
+    fn misspelt_keys_are_rejected() {
+        // `wen` would otherwise leave the rule without conditions: a catch-all
+        let typo = r#"{
+            "version": 1,
+            "rules": [{ "name": "hot", "wen": { "temperature_above_c": 90 }, "state": "PotatoMode" }],
+            "default_state": "GodMode"
+        }"#;
+        assert!(serde_json::from_str::<GovernorPolicy>(typo).is_err());
+        let top_level = r#"{ "version": 1, "rules": [], "default_state": "GodMode", "schedule": [] }"#;
+        assert!(serde_json::from_str::<GovernorPolicy>(top_level).is_err());
+        assert!(serde_json::from_str::<GovernorPolicy>(&typo.replace("wen", "when")).is_ok());
+    }
+
+    #[test]
+    fn undefined_categories_are_rejected() {
+        let policy: GovernorPolicy = serde_json::from_str(r#"{
+            "version": 1,
+            "rules": [{ "name": "render", "when": { "active": ["Production", "NotDefinedAnywhere"] }, "state": "SidekickMode" }],
+            "default_state": "GodMode"
+        }"#).unwrap();
+        let errors = policy.validate().unwrap_err();
+        assert!(errors[0].contains("isn't defined"), "{:?}", errors);
+    }
+
+    #[test]
+    fn bad_hysteresis_is_rejected() {
+        let with = |rules: Vec<DwellRule>| GovernorPolicy {
+            hysteresis: Some(Hysteresis { rules, ..Hysteresis::default() }),
+            ..Default::default()
+        };
+        let rule = |from: Option<GovernorState>, to: GovernorState, secs: f32| DwellRule { from, to, secs };
+        use GovernorState::*;
+
+        assert!(with(Hysteresis::default().rules).validate().is_ok());
+        assert!(with(vec![rule(Some(PotatoMode), SidekickMode, 2.0), rule(Some(GodMode), SidekickMode, 0.0)]).validate().is_ok());
+
+        let errors = |policy: GovernorPolicy| policy.validate().unwrap_err().len();
+        assert_eq!(errors(with(vec![rule(None, PotatoMode, -1.0)])), 1);
+        assert_eq!(errors(with(vec![rule(None, PotatoMode, f32::INFINITY)])), 1);
+        assert_eq!(errors(with(vec![rule(Some(SidekickMode), SidekickMode, 1.0)])), 1);
+        assert_eq!(errors(with(vec![rule(None, GodMode, 1.0)])), 1);
+        // Duplicates, and explicit downgrades behind a catch-all for the same target
+        assert_eq!(errors(with(vec![rule(None, PotatoMode, 0.0), rule(None, PotatoMode, 3.0)])), 1);
+        assert_eq!(errors(with(vec![rule(None, SidekickMode, 0.0), rule(Some(GodMode), SidekickMode, 3.0)])), 1);
+        let slow = GovernorPolicy {
+            hysteresis: Some(Hysteresis { upgrade_secs: -30.0, ..Hysteresis::default() }),
+            ..Default::default()
+        };
+        assert_eq!(errors(slow), 1);
+
+        // Unknown states and misspelt fields don't parse at all
+        assert!(serde_json::from_str::<Hysteresis>(r#"{"rules": [{"to": "TurboMode", "secs": 1}]}"#).is_err());
+        assert!(serde_json::from_str::<Hysteresis>(r#"{"rules": [{"form": "GodMode", "to": "PotatoMode", "secs": 1}]}"#).is_err());
+    }
+
+    #[test]
+    fn own_footprint_conditions_need_the_accounting() {
+        let when = Conditions { own_cpu_above: Some(150.0), ..Default::default() };
+        let mut i = inputs(8.0, Weekday::Mon, 12);
+        assert!(!when.matches(&i), "no self-accounting, no match");
+        i.own_cpu_percent = Some(90.0);
+        assert!(!when.matches(&i));
+        i.own_cpu_percent = Some(210.0);
+        assert!(when.matches(&i));
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::lobotomy::AppCategory;
+use crate::telemetry::TraceRecord;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FeatureStats {
+    seen: u32,     // Ticks the feature was present outside a game
+    followed: u32, // ...of which a game started within the horizon
+}
+
+/// THE PRECOG
+/// A frequency model over the Governor trace: for every feature (hour of day,
+/// open launcher, workspace, ...) how often a game started within `horizon`
+/// of it being seen. Features combine by noisy-OR, so one strong signal
+/// (Steam open at 21:00) is enough.
+#[derive(Debug, Clone)]
+pub struct Predictor {
+    features: HashMap<String, FeatureStats>,
+    min_support: u32,
+}
+
+impl Predictor {
+    pub fn train(records: &[TraceRecord], horizon: Duration, min_support: u32) -> Self {
+        // Game starts: first Game tick after a non-Game one. Overridden ticks are
+        // skipped: older traces used the flag for a simulated game.
+        let starts: Vec<u64> = records.windows(2)
+            .filter(|w| w[1].inputs.context == AppCategory::Game && w[0].inputs.context != AppCategory::Game)
+            .filter(|w| !w[1].override_active)
+            .map(|w| w[1].t_ms)
+            .collect();
+
+        let horizon_ms = horizon.as_millis() as u64;
+        let mut features: HashMap<String, FeatureStats> = HashMap::new();
+        let mut next_start = 0;
+
+        for record in records {
+            if record.inputs.context == AppCategory::Game || record.override_active {
+                continue;
+            }
+            while next_start < starts.len() && starts[next_start] <= record.t_ms {
+                next_start += 1;
+            }
+            let followed = starts.get(next_start).is_some_and(|t| *t - record.t_ms <= horizon_ms);
+
+            for feature in features_of(record.inputs.hour, &record.signals) {
+                let stats = features.entry(feature).or_default();
+                stats.seen += 1;
+                if followed { stats.followed += 1; }
+            }
+        }
+
+        Self { features, min_support }
+    }
+
+    /// Probability that a game starts within the horizon. None until the
+    /// trace holds enough history for any of the current features.
+    pub fn likelihood(&self, hour: u8, signals: &[String]) -> Option<f32> {
+        let mut none_fires = 1.0;
+        let mut known = false;
+        for feature in features_of(hour, signals) {
+            let Some(stats) = self.features.get(&feature) else { continue; };
+            if stats.seen < self.min_support { continue; }
+            known = true;
+            // Laplace smoothing keeps a handful of ticks from reading as certainty
+            let p = (stats.followed as f32 + 1.0) / (stats.seen as f32 + 2.0);
+            none_fires *= 1.0 - p;
+        }
+        known.then_some(1.0 - none_fires)
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+}
+
+fn features_of(hour: u8, signals: &[String]) -> Vec<String> {
+    std::iter::once(format!("hour:{}", hour))
+        .chain(signals.iter().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::GovernorState;
+    use crate::policy::GovernorInputs;
+
+    fn tick(t_ms: u64, hour: u8, context: AppCategory, signals: &[&str]) -> TraceRecord {
+        TraceRecord {
+            t_ms,
+            inputs: GovernorInputs { context, hour, ..GovernorInputs::idle() },
+            unknown_apps: Vec::new(),
+            signals: signals.iter().map(|s| s.to_string()).collect(),
+            override_active: false,
+            state: GovernorState::GodMode,
+            rule: String::new(),
+        }
+    }
+
+    /// A week of evenings: Steam opens at 21:00, a game follows a minute later.
+    /// Daytime coding never leads anywhere.
+    fn history() -> Vec<TraceRecord> {
+        let mut records = Vec::new();
+        for day in 0..7u64 {
+            let base = day * 86_400_000;
+            for s in 0..120 {
+                records.push(tick(base + 14 * 3_600_000 + s * 1000, 14, AppCategory::Development, &[]));
+            }
+            let evening = base + 21 * 3_600_000;
+            for s in 0..60 {
+                records.push(tick(evening + s * 1000, 21, AppCategory::System, &["launcher:steam"]));
+            }
+            for s in 60..120 {
+                records.push(tick(evening + s * 1000, 21, AppCategory::Game, &["launcher:steam"]));
+            }
+        }
+        records
+    }
+
+    #[test]
+    fn launcher_in_the_evening_predicts_a_game() {
+        let predictor = Predictor::train(&history(), Duration::from_secs(120), 20);
+        let steam = vec!["launcher:steam".to_string()];
+
+        let evening = predictor.likelihood(21, &steam).unwrap();
+        let afternoon = predictor.likelihood(14, &[]).unwrap();
+        assert!(evening > 0.9, "evening with Steam: {}", evening);
+        assert!(afternoon < 0.1, "afternoon: {}", afternoon);
+    }
+
+    #[test]
+    fn unknown_features_give_no_prediction() {
+        let predictor = Predictor::train(&history(), Duration::from_secs(120), 20);
+        assert_eq!(predictor.likelihood(3, &["workspace:9".to_string()]), None);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+
+/// One `some`/`full` line: share of wall time (percent) tasks spent stalled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StallAverages {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+}
+
+/// `some`: at least one task stalled. `full`: every non-idle task stalled
+/// (absent for system-wide CPU on older kernels).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pressure {
+    pub some: StallAverages,
+    pub full: Option<StallAverages>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PsiSnapshot {
+    pub cpu: Pressure,
+    pub memory: Pressure,
+    pub io: Pressure,
+}
+
+/// THE STALL GAUGE
+/// Pressure Stall Information answers "is anyone waiting?", which CPU percent
+/// and free RAM can't: a box at 100% CPU on batch work is fine, a box at 40%
+/// that is swapping is not.
+///
+/// Reads `<dir>/cpu`, `memory`, `io` (the /proc/pressure layout) or
+/// `<dir>/cpu.pressure`, ... (the cgroup v2 layout).
+pub struct PsiReader {
+    dir: PathBuf,
+    cgroup: bool,
+}
+
+impl PsiReader {
+    pub fn system() -> Self {
+        Self::at(PathBuf::from("/proc/pressure"), false)
+    }
+
+    pub fn at(dir: PathBuf, cgroup: bool) -> Self {
+        Self { dir, cgroup }
+    }
+
+    /// The user's session (`user@UID.service`) if it has PSI, so system
+    /// services grinding in the background don't count as the user suffering.
+    /// Falls back to system-wide PSI.
+    pub fn detect() -> Option<Self> {
+        let session = own_cgroup()
+            .and_then(|cg| session_ancestor(&cg))
+            .map(|cg| Self::at(Path::new("/sys/fs/cgroup").join(cg.trim_start_matches('/')), true))
+            .filter(|reader| reader.read().is_some());
+        if session.is_some() {
+            return session;
+        }
+        let system = Self::system();
+        system.read().map(|_| system)
+    }
+
+    pub fn scope(&self) -> &'static str {
+        if self.cgroup { "session cgroup" } else { "system" }
+    }
+
+    /// None when PSI is unavailable (non-Linux, kernel without CONFIG_PSI, psi=0).
+    pub fn read(&self) -> Option<PsiSnapshot> {
+        Some(PsiSnapshot {
+            cpu: self.read_resource("cpu")?,
+            memory: self.read_resource("memory")?,
+            io: self.read_resource("io")?,
+        })
+    }
+
+    fn read_resource(&self, resource: &str) -> Option<Pressure> {
+        let file = if self.cgroup { format!("{}.pressure", resource) } else { resource.to_string() };
+        parse(&fs::read_to_string(self.dir.join(file)).ok()?)
+    }
+}
+
+/// Parse the kernel format:
+///   some avg10=0.12 avg60=0.05 avg300=0.01 total=123456
+///   full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+pub fn parse(data: &str) -> Option<Pressure> {
+    let mut some = None;
+    let mut full = None;
+
+    for line in data.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let mut averages = StallAverages::default();
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else { continue; };
+            match key {
+                "avg10" => averages.avg10 = value.parse().ok()?,
+                "avg60" => averages.avg60 = value.parse().ok()?,
+                "avg300" => averages.avg300 = value.parse().ok()?,
+                _ => {}
+            }
+        }
+        match kind {
+            Some("some") => some = Some(averages),
+            Some("full") => full = Some(averages),
+            _ => {}
+        }
+    }
+
+    Some(Pressure { some: some?, full })
+}
+
+fn own_cgroup() -> Option<String> {
+    // cgroup v2 only: a single "0::/path" line
+    fs::read_to_string("/proc/self/cgroup").ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("0::").map(str::to_string))
+}
+
+fn session_ancestor(cgroup: &str) -> Option<String> {
+    let parts: Vec<&str> = cgroup.split('/').collect();
+    let end = parts.iter().position(|p| p.starts_with("user@") && p.ends_with(".service"))?;
+    Some(parts[..=end].join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/psi").join(name)
+    }
+
+    #[test]
+    fn reads_proc_layout() {
+        let snapshot = PsiReader::at(fixture("thrashing"), false).read().unwrap();
+        assert_eq!(snapshot.memory.some.avg10, 48.12);
+        assert_eq!(snapshot.memory.some.avg60, 31.5);
+        assert_eq!(snapshot.memory.full.unwrap().avg60, 22.04);
+        // Older kernels have no "full" line for system-wide CPU
+        assert_eq!(snapshot.cpu.full, None);
+    }
+
+    #[test]
+    fn reads_cgroup_layout() {
+        let snapshot = PsiReader::at(fixture("session"), true).read().unwrap();
+        assert_eq!(snapshot.io.some.avg60, 12.25);
+        assert_eq!(snapshot.cpu.full.unwrap().avg10, 0.0);
+    }
+
+    #[test]
+    fn missing_psi_is_none() {
+        assert!(PsiReader::at(fixture("does-not-exist"), false).read().is_none());
+        assert!(parse("garbage").is_none());
+    }
+
+    #[test]
+    fn finds_user_session() {
+        assert_eq!(
+            session_ancestor("/user.slice/user-1000.slice/user@1000.service/app.slice/cartesian.scope").as_deref(),
+            Some("/user.slice/user-1000.slice/user@1000.service")
+        );
+        assert_eq!(session_ancestor("/system.slice/sshd.service"), None);
+    }
+}
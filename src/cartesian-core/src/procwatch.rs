@@ -0,0 +1,376 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Process lifecycle since the last drain, by pid (threads are ignored).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcChanges {
+    pub started: HashSet<u32>,
+    pub execed: HashSet<u32>, // A new image under a pid we already had
+    pub exited: HashSet<u32>,
+    /// Events went missing (socket overrun, unreadable /proc): rescan everything.
+    pub lost: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcEvent {
+    Fork(u32),
+    Exec(u32),
+    Exit(u32),
+    /// The kernel's answer to the subscription; nonzero is an errno.
+    Ack(u32),
+}
+
+impl ProcChanges {
+    pub fn record(&mut self, event: ProcEvent) {
+        match event {
+            ProcEvent::Fork(pid) => {
+                self.started.insert(pid);
+                self.exited.remove(&pid);
+            }
+            ProcEvent::Exec(pid) => {
+                // A fresh pid is read in full anyway
+                if !self.started.contains(&pid) {
+                    self.execed.insert(pid);
+                }
+            }
+            ProcEvent::Exit(pid) => {
+                self.execed.remove(&pid);
+                // Came and went between two drains: nothing to tell
+                if !self.started.remove(&pid) {
+                    self.exited.insert(pid);
+                }
+            }
+            ProcEvent::Ack(_) => {}
+        }
+    }
+}
+
+/// Where Lobotomy learns that processes came or went.
+pub trait ProcessSource: Send {
+    fn name(&self) -> &str;
+    fn drain(&mut self) -> ProcChanges;
+}
+
+/// The proc connector if we may subscribe to it (needs CAP_NET_ADMIN),
+/// otherwise a poller diffing the /proc pid list.
+pub fn detect() -> Box<dyn ProcessSource> {
+    #[cfg(target_os = "linux")]
+    match connector::Connector::open() {
+        Ok(connector) => return Box::new(connector),
+        Err(e) => println!("Lobotomy: Proc connector unavailable ({}), polling /proc.", e),
+    }
+    Box::new(Poller::new(PathBuf::from("/proc")))
+}
+
+/// THE HEADCOUNT
+/// The unprivileged fallback: a readdir of /proc per drain, diffed against
+/// the last one. Far cheaper than re-reading every process, but blind to
+/// exec; the periodic rescan catches those.
+pub struct Poller {
+    root: PathBuf,
+    known: Option<HashSet<u32>>,
+}
+
+impl Poller {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, known: None }
+    }
+
+    fn list(&self) -> Option<HashSet<u32>> {
+        Some(fs::read_dir(&self.root).ok()?
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .collect())
+    }
+}
+
+impl ProcessSource for Poller {
+    fn name(&self) -> &str { "poll" }
+
+    fn drain(&mut self) -> ProcChanges {
+        let Some(now) = self.list() else {
+            // No /proc (Windows): every scan is a full one
+            return ProcChanges { lost: true, ..Default::default() };
+        };
+        let changes = match &self.known {
+            Some(known) => ProcChanges {
+                started: now.difference(known).copied().collect(),
+                exited: known.difference(&now).copied().collect(),
+                ..Default::default()
+            },
+            None => ProcChanges { lost: true, ..Default::default() },
+        };
+        self.known = Some(now);
+        changes
+    }
+}
+
+// --- PROC CONNECTOR WIRE FORMAT ---
+// nlmsghdr (16) + cn_msg (20) + proc_event header (what, cpu, timestamp: 16)
+const NLMSG_HDR_LEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+const EVENT_DATA: usize = NLMSG_HDR_LEN + CN_MSG_LEN + 16;
+const NLMSG_DONE: u16 = 3;
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_EVENT_NONE: u32 = 0;
+const PROC_EVENT_FORK: u32 = 0x1;
+const PROC_EVENT_EXEC: u32 = 0x2;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+fn read_u32(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Events in one datagram from the connector socket. Thread forks and exits
+/// (pid != tgid) are dropped; other event kinds (uid, comm, ...) too.
+pub fn parse(mut buf: &[u8]) -> Vec<ProcEvent> {
+    let mut events = Vec::new();
+    while let Some(len) = read_u32(buf, 0).map(|len| len as usize) {
+        if len < NLMSG_HDR_LEN || len > buf.len() {
+            break;
+        }
+        let message = &buf[..len];
+        let kind = u16::from_ne_bytes([message[4], message[5]]);
+        let proc = read_u32(message, NLMSG_HDR_LEN) == Some(CN_IDX_PROC)
+            && read_u32(message, NLMSG_HDR_LEN + 4) == Some(CN_VAL_PROC);
+        if kind == NLMSG_DONE && proc {
+            let data = |offset: usize| read_u32(message, EVENT_DATA + offset);
+            let event = match read_u32(message, NLMSG_HDR_LEN + CN_MSG_LEN) {
+                Some(PROC_EVENT_NONE) => data(0).map(ProcEvent::Ack),
+                // parent_pid, parent_tgid, child_pid, child_tgid
+                Some(PROC_EVENT_FORK) => data(8).zip(data(12))
+                    .and_then(|(pid, tgid)| (pid == tgid).then_some(ProcEvent::Fork(pid))),
+                // process_pid, process_tgid
+                Some(PROC_EVENT_EXEC) => data(4).map(ProcEvent::Exec),
+                Some(PROC_EVENT_EXIT) => data(0).zip(data(4))
+                    .and_then(|(pid, tgid)| (pid == tgid).then_some(ProcEvent::Exit(pid))),
+                _ => None,
+            };
+            events.extend(event);
+        }
+        // Messages are 4-byte aligned
+        buf = buf.get((len + 3) & !3..).unwrap_or_default();
+    }
+    events
+}
+
+/// The subscription request: PROC_CN_MCAST_LISTEN wrapped in cn_msg and nlmsghdr.
+fn listen_request(port: u32) -> Vec<u8> {
+    let total = NLMSG_HDR_LEN + CN_MSG_LEN + 4;
+    let mut message = Vec::with_capacity(total);
+    message.extend((total as u32).to_ne_bytes());
+    message.extend(NLMSG_DONE.to_ne_bytes());
+    message.extend(0u16.to_ne_bytes()); // flags
+    message.extend(0u32.to_ne_bytes()); // seq
+    message.extend(port.to_ne_bytes());
+    message.extend(CN_IDX_PROC.to_ne_bytes());
+    message.extend(CN_VAL_PROC.to_ne_bytes());
+    message.extend(0u32.to_ne_bytes()); // seq
+    message.extend(0u32.to_ne_bytes()); // ack
+    message.extend(4u16.to_ne_bytes()); // payload length
+    message.extend(0u16.to_ne_bytes()); // flags
+    message.extend(PROC_CN_MCAST_LISTEN.to_ne_bytes());
+    message
+}
+
+#[cfg(target_os = "linux")]
+mod connector {
+    use super::*;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::sync::{Arc, Mutex};
+
+    /// What the reader thread has seen since the last drain.
+    #[derive(Default)]
+    struct Shared {
+        changes: ProcChanges,
+        failed: bool,
+    }
+
+    /// THE WIRETAP
+    /// Subscribes to the kernel's proc connector (NETLINK_CONNECTOR,
+    /// CN_IDX_PROC) and collects fork/exec/exit events on a background
+    /// thread. Drains are a lock and a swap.
+    pub struct Connector {
+        shared: Arc<Mutex<Shared>>,
+    }
+
+    fn last_error(what: &str) -> String {
+        format!("{}: {}", what, io::Error::last_os_error())
+    }
+
+    fn set_option<T>(fd: &OwnedFd, option: libc::c_int, value: &T) -> Result<(), String> {
+        let result = unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(), libc::SOL_SOCKET, option,
+                value as *const T as *const libc::c_void, std::mem::size_of::<T>() as libc::socklen_t,
+            )
+        };
+        if result < 0 { Err(last_error("setsockopt")) } else { Ok(()) }
+    }
+
+    fn receive(fd: &OwnedFd, buf: &mut [u8]) -> io::Result<usize> {
+        let n = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+        if n < 0 { Err(io::Error::last_os_error()) } else { Ok(n as usize) }
+    }
+
+    impl Connector {
+        pub fn open() -> Result<Self, String> {
+            let raw = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_CONNECTOR) };
+            if raw < 0 {
+                return Err(last_error("socket"));
+            }
+            let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+            let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = CN_IDX_PROC;
+            let bound = unsafe {
+                libc::bind(fd.as_raw_fd(), &addr as *const _ as *const libc::sockaddr,
+                    std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t)
+            };
+            if bound < 0 {
+                return Err(last_error("bind")); // EPERM without CAP_NET_ADMIN
+            }
+
+            // Room for a fork storm; an overrun only costs a rescan
+            let rcvbuf: libc::c_int = 1 << 20;
+            set_option(&fd, libc::SO_RCVBUF, &rcvbuf)?;
+            let timeout = libc::timeval { tv_sec: 1, tv_usec: 0 };
+            set_option(&fd, libc::SO_RCVTIMEO, &timeout)?;
+
+            let request = listen_request(std::process::id());
+            let sent = unsafe { libc::send(fd.as_raw_fd(), request.as_ptr() as *const libc::c_void, request.len(), 0) };
+            if sent < 0 {
+                return Err(last_error("send"));
+            }
+
+            // The ack comes first; events may already follow it
+            let mut buf = vec![0u8; 4096];
+            let mut changes = ProcChanges::default();
+            let mut acked = false;
+            while !acked {
+                let n = receive(&fd, &mut buf).map_err(|e| format!("no acknowledgement: {}", e))?;
+                for event in parse(&buf[..n]) {
+                    match event {
+                        ProcEvent::Ack(0) => acked = true,
+                        ProcEvent::Ack(errno) => return Err(format!("subscription refused: {}", io::Error::from_raw_os_error(errno as i32))),
+                        event => changes.record(event),
+                    }
+                }
+            }
+            set_option(&fd, libc::SO_RCVTIMEO, &libc::timeval { tv_sec: 0, tv_usec: 0 })?;
+
+            let shared = Arc::new(Mutex::new(Shared { changes, failed: false }));
+            let writer = shared.clone();
+            std::thread::spawn(move || listen(fd, buf, writer));
+            Ok(Self { shared })
+        }
+    }
+
+    fn listen(fd: OwnedFd, mut buf: Vec<u8>, shared: Arc<Mutex<Shared>>) {
+        loop {
+            let received = receive(&fd, &mut buf);
+            let Ok(mut shared) = shared.lock() else { return; };
+            match received {
+                Ok(n) => parse(&buf[..n]).into_iter().for_each(|event| shared.changes.record(event)),
+                // The kernel dropped events for us: the table can't be trusted
+                Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => shared.changes.lost = true,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    eprintln!("Lobotomy: Proc connector failed, falling back to full scans: {}", e);
+                    shared.failed = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    impl ProcessSource for Connector {
+        fn name(&self) -> &str { "proc connector" }
+
+        fn drain(&mut self) -> ProcChanges {
+            let Ok(mut shared) = self.shared.lock() else {
+                return ProcChanges { lost: true, ..Default::default() };
+            };
+            let mut changes = std::mem::take(&mut shared.changes);
+            changes.lost |= shared.failed;
+            changes
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One connector datagram carrying `what` with `data` as the event payload.
+    fn datagram(what: u32, data: &[u32]) -> Vec<u8> {
+        let mut message = listen_request(0);
+        message.truncate(NLMSG_HDR_LEN + CN_MSG_LEN);
+        message.extend(what.to_ne_bytes());
+        message.extend(0u32.to_ne_bytes()); // cpu
+        message.extend(0u64.to_ne_bytes()); // timestamp
+        data.iter().for_each(|word| message.extend(word.to_ne_bytes()));
+        let len = message.len() as u32;
+        message[..4].copy_from_slice(&len.to_ne_bytes());
+        message
+    }
+
+    #[test]
+    fn connector_events_parse() {
+        assert_eq!(parse(&datagram(PROC_EVENT_NONE, &[0])), vec![ProcEvent::Ack(0)]);
+        assert_eq!(parse(&datagram(PROC_EVENT_FORK, &[1, 1, 300, 300])), vec![ProcEvent::Fork(300)]);
+        assert_eq!(parse(&datagram(PROC_EVENT_FORK, &[300, 300, 301, 300])), vec![], "thread spawn");
+        assert_eq!(parse(&datagram(PROC_EVENT_EXEC, &[300, 300])), vec![ProcEvent::Exec(300)]);
+        assert_eq!(parse(&datagram(PROC_EVENT_EXIT, &[300, 300, 0, 17])), vec![ProcEvent::Exit(300)]);
+        assert_eq!(parse(&datagram(0x200, &[300, 300])), vec![], "comm change");
+        assert_eq!(parse(&[1, 2, 3]), vec![]);
+
+        let mut two = datagram(PROC_EVENT_FORK, &[1, 1, 400, 400]);
+        two.extend(datagram(PROC_EVENT_EXIT, &[400, 400, 0, 17]));
+        assert_eq!(parse(&two), vec![ProcEvent::Fork(400), ProcEvent::Exit(400)]);
+    }
+
+    #[test]
+    fn changes_collapse_between_drains() {
+        let mut changes = ProcChanges::default();
+        [ProcEvent::Fork(10), ProcEvent::Exec(10), ProcEvent::Exec(20), ProcEvent::Exit(30),
+         ProcEvent::Fork(40), ProcEvent::Exit(40)]
+            .into_iter().for_each(|event| changes.record(event));
+
+        assert_eq!(changes.started, HashSet::from([10]));
+        assert_eq!(changes.execed, HashSet::from([20]));
+        assert_eq!(changes.exited, HashSet::from([30]), "40 came and went");
+
+        // Pid reuse: the old process exits, a new one takes its number
+        changes.record(ProcEvent::Exit(20));
+        changes.record(ProcEvent::Fork(30));
+        assert!(changes.execed.is_empty());
+        assert_eq!(changes.exited, HashSet::from([20]));
+        assert!(changes.started.contains(&30));
+    }
+
+    #[test]
+    fn poller_diffs_the_pid_list() {
+        let root = std::env::temp_dir().join(format!("cartesian-proc-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["1", "200", "self", "sys"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let mut poller = Poller::new(root.clone());
+        assert!(poller.drain().lost, "first drain has nothing to diff against");
+
+        fs::create_dir(root.join("300")).unwrap();
+        fs::remove_dir(root.join("200")).unwrap();
+        let changes = poller.drain();
+        assert_eq!((changes.started, changes.exited, changes.lost), (HashSet::from([300]), HashSet::from([200]), false));
+        assert_eq!(poller.drain(), ProcChanges::default());
+
+        fs::remove_dir_all(&root).unwrap();
+        assert!(poller.drain().lost);
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Serialize, Deserialize};
+
+use crate::config;
+use crate::classify::{ClassRule, ProcessMatch};
+use crate::inference::GovernorState;
+use crate::lobotomy::AppCategory;
+
+pub const REGISTRY_VERSION: u32 = 1;
+/// Classification rules from registry entries are named `registry:<app>`.
+pub const RULE_PREFIX: &str = "registry:";
+
+/// What the registry knows about one app, keyed by its (lowercase) process name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppEntry {
+    pub category: AppCategory,
+    /// Governor hint: the highest state allowed while the app runs.
+    /// `SidekickMode` means "never load the Manager while this runs".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_state: Option<GovernorState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Match by more than the process name (exe path, Flatpak id, ...).
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub when: Option<ProcessMatch>,
+}
+
+impl AppEntry {
+    pub fn new(category: AppCategory) -> Self {
+        Self { category, max_state: None, note: None, when: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryFile {
+    pub version: u32,
+    #[serde(default)]
+    pub apps: BTreeMap<String, AppEntry>,
+}
+
+impl Default for RegistryFile {
+    fn default() -> Self {
+        Self { version: REGISTRY_VERSION, apps: BTreeMap::new() }
+    }
+}
+
+impl RegistryFile {
+    /// Versioned files as they are; the old flat `name -> category` map is
+    /// migrated (the bool says so).
+    pub fn parse(data: &str) -> Result<(Self, bool), String> {
+        let value: serde_json::Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        if value.get("version").is_none() {
+            let flat: HashMap<String, AppCategory> = serde_json::from_value(value).map_err(|e| e.to_string())?;
+            let apps = flat.into_iter()
+                .map(|(name, category)| (name.to_ascii_lowercase(), AppEntry::new(category)))
+                .collect();
+            return Ok((Self { version: REGISTRY_VERSION, apps }, true));
+        }
+
+        let file: Self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        file.validate().map_err(|errors| errors.join("; "))?;
+        Ok((file, false))
+    }
+
+    /// None if the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Option<(Self, bool)>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("{:?}: {}", path, e))?;
+        Self::parse(&data).map(Some).map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{:?}: {}", dir, e))?;
+        }
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, data).map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.version != REGISTRY_VERSION {
+            errors.push(format!("unsupported version {} (expected {})", self.version, REGISTRY_VERSION));
+        }
+        for (name, entry) in &self.apps {
+            if name.is_empty() {
+                errors.push("app with an empty name".to_string());
+            } else if *name != name.to_ascii_lowercase() {
+                errors.push(format!("app '{}': names are lowercase process names", name));
+            }
+            if entry.when.as_ref().is_some_and(|when| when.is_empty()) {
+                errors.push(format!("app '{}': empty match", name));
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// THE PHONE BOOK
+/// What Cartesian knows about apps beyond the built-in rules: a category,
+/// an optional Governor ceiling and a note per app, optionally with a match
+/// rule. The system-wide file (a team's shared registry) is read first and
+/// the user's file overrides it entry by entry. Both are watched; the user's
+/// answers from the dashboard are written to the user's file only.
+pub struct Registry {
+    user_path: PathBuf,
+    system_path: PathBuf,
+    user: RegistryFile,
+    system: RegistryFile,
+    user_rejected: bool, // The file on disk isn't what `user` holds
+    merged: BTreeMap<String, AppEntry>,
+    stamps: [Option<SystemTime>; 2],
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Registry {
+    /// The registry in the config dir, after migrating `legacy` (the old
+    /// flat file in the working directory) if there is no registry yet.
+    pub fn open(user_path: PathBuf, system_path: PathBuf, legacy: &Path) -> Self {
+        if !user_path.exists() && legacy.exists() {
+            match RegistryFile::load(legacy) {
+                Ok(Some((file, _))) => match file.save(&user_path) {
+                    Ok(()) => println!("Lobotomy: Migrated {} apps from {:?} to {:?}.", file.apps.len(), legacy, user_path),
+                    Err(e) => eprintln!("Lobotomy: Registry migration failed: {}", e),
+                },
+                Ok(None) => {}
+                Err(e) => eprintln!("Lobotomy: Old registry unreadable, not migrated: {}", e),
+            }
+        }
+
+        let mut registry = Self {
+            user_path,
+            system_path,
+            user: RegistryFile::default(),
+            system: RegistryFile::default(),
+            user_rejected: false,
+            merged: BTreeMap::new(),
+            stamps: [None, None],
+        };
+        if let Err(e) = registry.reload() {
+            eprintln!("Lobotomy: {}", e);
+        }
+        registry
+    }
+
+    /// Re-read both files. A broken file keeps its previous entries.
+    fn reload(&mut self) -> Result<(), String> {
+        // Taken before reading, so an edit made meanwhile shows up on the next poll
+        let stamps = [modified(&self.user_path), modified(&self.system_path)];
+        let mut errors = Vec::new();
+
+        match RegistryFile::load(&self.system_path) {
+            Ok(file) => self.system = file.map(|(file, _)| file).unwrap_or_default(),
+            Err(e) => errors.push(format!("System registry rejected, keeping previous: {}", e)),
+        }
+        let mut migrated = false;
+        match RegistryFile::load(&self.user_path) {
+            Ok(file) => {
+                (self.user, migrated) = file.unwrap_or_default();
+                self.user_rejected = false;
+            }
+            Err(e) => {
+                self.user_rejected = true;
+                errors.push(format!("Registry rejected, keeping previous: {}", e));
+            }
+        }
+        self.stamps = stamps;
+
+        if migrated {
+            // An unversioned file in the new place: upgrade it in place
+            match self.save() {
+                Ok(()) => println!("Lobotomy: Registry {:?} upgraded to version {}.", self.user_path, REGISTRY_VERSION),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        self.merged = self.system.apps.clone();
+        self.merged.extend(self.user.apps.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+        if self.merged.is_empty() {
+            self.merged.insert("firefox".into(), AppEntry::new(AppCategory::Web));
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) }
+    }
+
+    /// Some(..) when either file appeared, changed or went away since the last call.
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        let stamps = [modified(&self.user_path), modified(&self.system_path)];
+        if stamps == self.stamps {
+            return None;
+        }
+        Some(self.reload())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AppEntry> {
+        self.merged.get(name)
+    }
+
+    /// Classification rules for the entries that carry a match.
+    pub fn rules(&self) -> Vec<ClassRule> {
+        self.merged.iter()
+            .filter_map(|(name, entry)| Some(ClassRule {
+                name: format!("{}{}", RULE_PREFIX, name),
+                priority: config::REGISTRY_RULE_PRIORITY,
+                when: entry.when.clone()?,
+                category: Some(entry.category),
+                launcher: None,
+            }))
+            .collect()
+    }
+
+    /// The user's answer for an unknown app; keeps any hint or note already there.
+    /// Refused while the user's file is rejected: saving would overwrite their edit.
+    pub fn learn(&mut self, name: &str, category: AppCategory) -> Result<(), String> {
+        if self.user_rejected {
+            return Err(format!("{:?} has errors; fix it before adding apps", self.user_path));
+        }
+        let name = name.to_ascii_lowercase();
+        let entry = self.user.apps.entry(name.clone())
+            .or_insert_with(|| self.merged.get(&name).cloned().unwrap_or_else(|| AppEntry::new(category)));
+        entry.category = category;
+        self.merged.insert(name, entry.clone());
+        self.save()
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        self.user.save(&self.user_path)?;
+        // Our own write is not a change to reload
+        self.stamps[0] = modified(&self.user_path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cartesian-registry-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn flat_registry_migrates() {
+        let (file, migrated) = RegistryFile::parse(r#"{"Blender": "Production", "mpv": "Media"}"#).unwrap();
+        assert!(migrated);
+        assert_eq!(file.version, REGISTRY_VERSION);
+        assert_eq!((file.apps["blender"].category, file.apps["blender"].max_state), (AppCategory::Production, None));
+
+        let (file, migrated) = RegistryFile::parse(r#"{
+            "version": 1,
+            "apps": {
+                "blender": {"category": "Production", "max_state": "SidekickMode", "note": "renders eat all VRAM"},
+                "resolve": {"category": "Production", "match": {"exe": "/opt/resolve/bin/*"}}
+            }
+        }"#).unwrap();
+        assert!(!migrated);
+        assert_eq!(file.apps["blender"].max_state, Some(GovernorState::SidekickMode));
+
+        // A category removed from categories.json since
+        let (file, _) = RegistryFile::parse(r#"{"version": 1, "apps": {"obs": {"category": "Streaming"}}}"#).unwrap();
+        assert_eq!(file.apps["obs"].category, AppCategory::Unknown);
+
+        assert!(RegistryFile::parse(r#"{"version": 2, "apps": {}}"#).is_err());
+        assert!(RegistryFile::parse(r#"{"version": 1, "apps": {"x": {"category": "Web", "match": {}}}}"#).is_err());
+    }
+
+    #[test]
+    fn user_entries_override_the_system_registry() {
+        let dir = temp_dir("merge");
+        let (user, system, legacy) = (dir.join("user.json"), dir.join("system.json"), dir.join("legacy.json"));
+        fs::write(&system, r#"{"version": 1, "apps": {
+            "blender": {"category": "Production", "max_state": "SidekickMode"},
+            "resolve": {"category": "Production", "match": {"exe": "/opt/resolve/bin/*"}}
+        }}"#).unwrap();
+        fs::write(&legacy, r#"{"blender": "Game", "mpv": "Media"}"#).unwrap();
+
+        let mut registry = Registry::open(user.clone(), system, &legacy);
+        assert!(user.exists(), "legacy file migrated");
+        assert_eq!(registry.get("blender").map(|e| e.category), Some(AppCategory::Game));
+        assert_eq!(registry.get("mpv").map(|e| e.category), Some(AppCategory::Media));
+        assert_eq!(registry.rules().iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["registry:resolve"]);
+
+        // Learning keeps the system entry's hint but writes only to the user file
+        registry.learn("Resolve", AppCategory::Development).unwrap();
+        assert_eq!(registry.poll(), None, "own write is not a change");
+        let (saved, _) = RegistryFile::load(&user).unwrap().unwrap();
+        assert_eq!(saved.apps["resolve"].category, AppCategory::Development);
+        assert!(saved.apps["resolve"].when.is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn edits_reload_and_broken_edits_keep_the_last_good_entries() {
+        let dir = temp_dir("reload");
+        let user = dir.join("user.json");
+        let mut registry = Registry::open(user.clone(), dir.join("none.json"), &dir.join("none-legacy.json"));
+        assert_eq!(registry.get("firefox").map(|e| e.category), Some(AppCategory::Web), "built-in default");
+
+        fs::write(&user, r#"{"version": 1, "apps": {"krita": {"category": "Production"}}}"#).unwrap();
+        assert_eq!(registry.poll(), Some(Ok(())));
+        assert_eq!(registry.get("krita").map(|e| e.category), Some(AppCategory::Production));
+        assert!(registry.get("firefox").is_none());
+
+        // Let the mtime move on coarse filesystems
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(&user, "{ not json").unwrap();
+        assert!(matches!(registry.poll(), Some(Err(_))));
+        assert!(registry.get("krita").is_some());
+
+        // The dashboard must not save the last good copy over the user's edit
+        assert!(registry.learn("mpv", AppCategory::Media).is_err());
+        assert_eq!(fs::read_to_string(&user).unwrap(), "{ not json");
+        fs::write(&user, r#"{"version": 1, "apps": {}}"#).unwrap();
+        assert_eq!(registry.poll(), Some(Ok(())));
+        registry.learn("mpv", AppCategory::Media).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_broken_system_file_does_not_hide_user_edits() {
+        let dir = temp_dir("both");
+        let (user, system) = (dir.join("user.json"), dir.join("system.json"));
+        fs::write(&system, r#"{"version": 1, "apps": {"blender": {"category": "Production"}}}"#).unwrap();
+        let mut registry = Registry::open(user.clone(), system.clone(), &dir.join("none-legacy.json"));
+        assert_eq!(registry.get("blender").map(|e| e.category), Some(AppCategory::Production));
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(&system, "{ not json").unwrap();
+        fs::write(&user, r#"{"version": 1, "apps": {"krita": {"category": "Production"}}}"#).unwrap();
+        assert!(matches!(registry.poll(), Some(Err(_))));
+        assert!(registry.get("krita").is_some(), "user edit picked up");
+        assert!(registry.get("blender").is_some(), "system entries kept");
+        assert_eq!(registry.poll(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,466 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+use crate::config;
+use crate::inference::BackgroundBudget;
+
+/// Work the core can pause on its own, without stopping the process (and the UI with it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Work {
+    Capture,       // Witness frames and their OCR
+    Indexing,      // Hippocampus files and visual engrams
+    Consolidation, // LTP batches (flushes carry on)
+    Generation,    // Chat replies
+}
+
+pub const WORK: [Work; 4] = [Work::Capture, Work::Indexing, Work::Consolidation, Work::Generation];
+
+impl Work {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Work::Capture => "capture",
+            Work::Indexing => "indexing",
+            Work::Consolidation => "consolidation",
+            Work::Generation => "generation",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        WORK.into_iter().find(|w| w.name() == name)
+    }
+}
+
+/// Who holds a pause. Each keeps its own: the Governor lifting its pause
+/// doesn't undo the admin's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Requester {
+    Governor,
+    Admin,
+}
+
+impl Requester {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Requester::Governor => "GOVERNOR",
+            Requester::Admin => "ADMIN",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoClass {
+    BestEffort(u8), // 0 (first) to 7 (last)
+    Idle,           // Only when nobody else wants the disk
+}
+
+/// Scheduling priority of a worker thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Priority {
+    pub nice: i32,
+    pub io: IoClass,
+}
+
+impl Priority {
+    pub const NORMAL: Self = Self { nice: 0, io: IoClass::BestEffort(4) };
+    pub const LEASHED: Self = Self { nice: 19, io: IoClass::Idle };
+
+    pub fn for_budget(budget: BackgroundBudget) -> Self {
+        match budget {
+            BackgroundBudget::Full => Self::NORMAL,
+            BackgroundBudget::Throttled => Self { nice: config::THROTTLED_NICE, io: IoClass::BestEffort(7) },
+            BackgroundBudget::Paused => Self::LEASHED,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    paused: BTreeMap<Work, BTreeSet<Requester>>,
+    threads: BTreeMap<Work, Vec<i32>>, // Kernel thread ids
+    wanted: BTreeMap<Work, Priority>,  // The Governor's
+    applied: BTreeMap<Work, Priority>, // Reached by every thread; anything else is retried
+    moved: BTreeSet<Work>,             // Ever moved off NORMAL
+    leashed: bool,                     // The admin's: every worker at LEASHED
+}
+
+/// THE BRAKES
+/// Cooperative pause and resume per kind of work, plus nice/ioprio for the
+/// worker threads doing it. Nothing is stopped from outside: workers check
+/// `is_paused` between units of work and idle while it holds. Pauses come
+/// from the Governor (every tick) or the admin bridge (the control socket).
+pub struct ResourceController {
+    state: Mutex<State>,
+}
+
+static CONTROLLER: LazyLock<ResourceController> = LazyLock::new(ResourceController::new);
+
+pub fn controller() -> &'static ResourceController {
+    &CONTROLLER
+}
+
+impl ResourceController {
+    pub fn new() -> Self {
+        Self { state: Mutex::new(State::default()) }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hold or lift `by`'s pause on `work`. Returns whether anything changed.
+    pub fn set_paused(&self, work: Work, by: Requester, paused: bool) -> bool {
+        let mut state = self.state();
+        let holders = state.paused.entry(work).or_default();
+        if paused { holders.insert(by) } else { holders.remove(&by) }
+    }
+
+    pub fn is_paused(&self, work: Work) -> bool {
+        self.state().paused.get(&work).is_some_and(|holders| !holders.is_empty())
+    }
+
+    pub fn paused_by(&self, work: Work) -> Vec<Requester> {
+        self.state().paused.get(&work).map(|holders| holders.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// Count the calling thread as one of `work`'s workers, for good (dedicated threads only).
+    pub fn join(&self, work: Work) {
+        let Some(tid) = thread_id() else { return; };
+        let mut state = self.state();
+        state.threads.entry(work).or_default().push(tid);
+        // A thread that joins late starts at the current priority
+        if state.moved.contains(&work) {
+            if let Err(e) = set_thread_priority(tid, effective(&state, work)) {
+                eprintln!("Resources: {}", e);
+            }
+        }
+    }
+
+    /// The Governor's priority for `work`'s threads. Applied unless leashed;
+    /// a no-op if nothing changed.
+    pub fn set_priority(&self, work: Work, priority: Priority) -> Result<(), String> {
+        let mut state = self.state();
+        state.wanted.insert(work, priority);
+        apply(&mut state)
+    }
+
+    /// Push every worker thread to LEASHED, whatever the Governor wants, or let go.
+    pub fn set_leashed(&self, leashed: bool) -> Result<(), String> {
+        let mut state = self.state();
+        state.leashed = leashed;
+        apply(&mut state)
+    }
+
+    pub fn is_leashed(&self) -> bool {
+        self.state().leashed
+    }
+
+    /// `capture=running indexing=paused(admin) ...`, plus `leashed` if so.
+    pub fn status(&self) -> String {
+        let mut parts: Vec<String> = WORK.iter().map(|work| match self.paused_by(*work).as_slice() {
+            [] => format!("{}=running", work.name()),
+            by => format!(
+                "{}=paused({})",
+                work.name(),
+                by.iter().map(|r| r.label().to_ascii_lowercase()).collect::<Vec<_>>().join(","),
+            ),
+        }).collect();
+        if self.is_leashed() {
+            parts.push("leashed".to_string());
+        }
+        parts.join(" ")
+    }
+
+    /// One control-socket command from the admin bridge; the reply is one line.
+    ///
+    ///   status | pause <work|all> | resume <work|all> | leash | unleash
+    pub fn handle(&self, command: &str) -> String {
+        let result = match command.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["status"] => Ok(()),
+            [verb @ ("pause" | "resume"), target] => {
+                let works = match *target {
+                    "all" => Ok(WORK.to_vec()),
+                    name => Work::parse(name).map(|w| vec![w]).ok_or_else(|| format!("unknown work '{}'", name)),
+                };
+                works.map(|works| {
+                    for work in works {
+                        if self.set_paused(work, Requester::Admin, *verb == "pause") {
+                            println!("Resources: {} {} by admin", work.name(), if *verb == "pause" { "paused" } else { "resumed" });
+                        }
+                    }
+                })
+            }
+            ["leash"] => self.set_leashed(true),
+            ["unleash"] => self.set_leashed(false),
+            _ => Err(format!("unknown command '{}'", command.trim())),
+        };
+        match result {
+            Ok(()) => format!("OK {}", self.status()),
+            Err(e) => format!("ERR {}", e),
+        }
+    }
+}
+
+fn effective(state: &State, work: Work) -> Priority {
+    if state.leashed {
+        Priority::LEASHED
+    } else {
+        state.wanted.get(&work).copied().unwrap_or(Priority::NORMAL)
+    }
+}
+
+/// Bring every worker thread to its effective priority, where it changed.
+/// A work whose threads didn't all get there is tried again on the next call.
+fn apply(state: &mut State) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for work in WORK {
+        let effective = effective(state, work);
+        if state.applied.get(&work) == Some(&effective) {
+            continue;
+        }
+        // Don't touch threads that were never moved off the default
+        if !state.moved.contains(&work) && effective == Priority::NORMAL {
+            continue;
+        }
+        state.moved.insert(work);
+        state.applied.remove(&work);
+        let failed = errors.len();
+        if let Some(threads) = state.threads.get_mut(&work) {
+            threads.retain(|tid| match set_thread_priority(*tid, effective) {
+                Ok(()) => true,
+                Err(e) if e.ends_with(GONE) => false, // Thread exited
+                Err(e) => {
+                    errors.push(e);
+                    true
+                }
+            });
+        }
+        if errors.len() == failed {
+            state.applied.insert(work, effective);
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) }
+}
+
+const GONE: &str = "no such thread";
+
+/// The calling thread's kernel id, as cgroup.threads and setpriority want it.
+#[cfg(target_os = "linux")]
+pub fn thread_id() -> Option<i32> {
+    Some(unsafe { libc::syscall(libc::SYS_gettid) } as i32)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn thread_id() -> Option<i32> {
+    None
+}
+
+/// Lowering nice again needs root or an RLIMIT_NICE of at least 20, which a
+/// desktop session rarely has. Without it nice is left alone: CPU is held back
+/// by the cgroup and pauses, and a thread never gets stuck at nice 19.
+#[cfg(target_os = "linux")]
+fn nice_is_reversible() -> bool {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NICE, &mut limit) } != 0 {
+        return false;
+    }
+    let root = unsafe { libc::geteuid() } == 0;
+    root || limit.rlim_cur == libc::RLIM_INFINITY || limit.rlim_cur >= 20
+}
+
+/// nice and ioprio are per thread on Linux. Each is set on its own, so a
+/// refused nice change doesn't leave the thread's I/O class behind.
+#[cfg(target_os = "linux")]
+fn set_thread_priority(tid: i32, priority: Priority) -> Result<(), String> {
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: u32 = 13;
+    let ioprio = match priority.io {
+        IoClass::BestEffort(level) => (2 << IOPRIO_CLASS_SHIFT) | level.min(7) as libc::c_int,
+        IoClass::Idle => 3 << IOPRIO_CLASS_SHIFT,
+    };
+    let error = |what: &str| {
+        let e = std::io::Error::last_os_error();
+        match e.raw_os_error() {
+            Some(libc::ESRCH) => format!("thread {}: {}", tid, GONE),
+            _ => format!("thread {}: {}: {}", tid, what, e),
+        }
+    };
+
+    let mut errors = Vec::new();
+    if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, ioprio) } != 0 {
+        errors.push(error("ioprio"));
+    }
+    if nice_is_reversible() && unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, priority.nice) } != 0 {
+        errors.push(error("nice"));
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_thread_priority(_tid: i32, _priority: Priority) -> Result<(), String> {
+    Ok(())
+}
+
+// --- THE CONTROL SOCKET ---
+
+/// Answer control commands on `path` from a background thread. The socket
+/// is owner-only; root (the admin bridge) connects regardless.
+pub fn serve(path: &Path) -> Result<(), String> {
+    serve_for(path, controller())
+}
+
+#[cfg(unix)]
+fn serve_for(path: &Path, brakes: &'static ResourceController) -> Result<(), String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    // A socket left behind by a crashed run
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path).map_err(|e| format!("{:?}: {}", path, e))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).map_err(|e| format!("{:?}: {}", path, e))?;
+
+    std::thread::Builder::new()
+        .name("cartesian-control".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue; };
+                let _ = stream.set_read_timeout(Some(config::CONTROL_TIMEOUT));
+                let mut line = String::new();
+                if BufReader::new(&stream).read_line(&mut line).is_err() {
+                    continue;
+                }
+                let reply = brakes.handle(&line);
+                let _ = writeln!(stream, "{}", reply);
+            }
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to spawn control thread: {}", e))
+}
+
+#[cfg(not(unix))]
+fn serve_for(_path: &Path, _brakes: &'static ResourceController) -> Result<(), String> {
+    Err("The control socket needs a Unix platform".to_string())
+}
+
+/// Send one command to a running core and return its reply.
+#[cfg(unix)]
+pub fn request(path: &Path, command: &str) -> Result<String, String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path).map_err(|e| format!("{:?}: {} (is cartesian-core running?)", path, e))?;
+    stream.set_read_timeout(Some(config::CONTROL_TIMEOUT)).map_err(|e| e.to_string())?;
+    writeln!(stream, "{}", command).map_err(|e| e.to_string())?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).map_err(|e| e.to_string())?;
+    Ok(reply.trim_end().to_string())
+}
+
+#[cfg(not(unix))]
+pub fn request(_path: &Path, _command: &str) -> Result<String, String> {
+    Err("The control socket needs a Unix platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_belong_to_their_requester() {
+        let brakes = ResourceController::new();
+        assert!(!brakes.is_paused(Work::Indexing));
+
+        assert!(brakes.set_paused(Work::Indexing, Requester::Governor, true));
+        assert!(brakes.set_paused(Work::Indexing, Requester::Admin, true));
+        assert!(!brakes.set_paused(Work::Indexing, Requester::Admin, true), "already held");
+        assert_eq!(brakes.paused_by(Work::Indexing), vec![Requester::Governor, Requester::Admin]);
+
+        // The Governor letting go leaves the admin's pause
+        brakes.set_paused(Work::Indexing, Requester::Governor, false);
+        assert!(brakes.is_paused(Work::Indexing));
+        brakes.set_paused(Work::Indexing, Requester::Admin, false);
+        assert!(!brakes.is_paused(Work::Indexing));
+        assert!(!brakes.is_paused(Work::Capture));
+    }
+
+    #[test]
+    fn control_commands() {
+        let brakes = ResourceController::new();
+        assert_eq!(
+            brakes.handle("pause capture\n"),
+            "OK capture=paused(admin) indexing=running consolidation=running generation=running",
+        );
+        brakes.set_paused(Work::Capture, Requester::Governor, true);
+        assert!(brakes.handle("status").contains("capture=paused(governor,admin)"));
+
+        assert!(brakes.handle("pause all").starts_with("OK"));
+        assert!(WORK.iter().all(|w| brakes.is_paused(*w)));
+        brakes.handle("resume all");
+        assert_eq!(brakes.paused_by(Work::Capture), vec![Requester::Governor]);
+        assert!(!brakes.is_paused(Work::Generation));
+
+        assert!(brakes.handle("pause everything").starts_with("ERR"));
+        assert!(brakes.handle("reboot").starts_with("ERR"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn control_socket_round_trip() {
+        let path = std::env::temp_dir().join(format!("cartesian-control-{}.sock", std::process::id()));
+        // Not the process-wide controller: other tests run alongside
+        let brakes: &'static ResourceController = Box::leak(Box::new(ResourceController::new()));
+        serve_for(&path, brakes).unwrap();
+        assert!(request(&path, "pause generation").unwrap().contains("generation=paused(admin)"));
+        assert!(brakes.is_paused(Work::Generation));
+        request(&path, "resume generation").unwrap();
+        assert!(!brakes.is_paused(Work::Generation));
+        assert!(request(&path, "pause the world").unwrap().starts_with("ERR"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn worker_threads_are_reniced() {
+        use std::sync::mpsc;
+        let brakes = std::sync::Arc::new(ResourceController::new());
+        let (joined, wait_joined) = mpsc::channel();
+        let (done, wait_done) = mpsc::channel::<()>();
+        let worker = {
+            let brakes = brakes.clone();
+            std::thread::spawn(move || {
+                brakes.join(Work::Indexing);
+                joined.send(thread_id().unwrap()).unwrap();
+                let _ = wait_done.recv();
+            })
+        };
+        let tid = wait_joined.recv().unwrap();
+        let nice = || unsafe { libc::getpriority(libc::PRIO_PROCESS, tid as libc::id_t) };
+        let ioprio = || unsafe { libc::syscall(libc::SYS_ioprio_get, 1, tid) } as i32;
+        let before = nice();
+        // Without the right to undo it, nice stays where it was
+        let reniced = |n: i32| if nice_is_reversible() { n } else { before };
+
+        brakes.set_priority(Work::Indexing, Priority { nice: 15, io: IoClass::BestEffort(7) }).unwrap();
+        assert_eq!(nice(), reniced(15));
+        assert_eq!(ioprio(), (2 << 13) | 7);
+        brakes.set_leashed(true).unwrap();
+        assert_eq!(nice(), reniced(19));
+        assert_eq!(ioprio(), 3 << 13);
+        // The main thread was never touched
+        assert_eq!(unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }, before);
+
+        // Back from the leash, I/O first
+        brakes.set_leashed(false).unwrap();
+        brakes.set_priority(Work::Indexing, Priority::NORMAL).unwrap();
+        assert_eq!(ioprio(), (2 << 13) | 4);
+        assert_eq!(nice(), reniced(0));
+        brakes.set_leashed(true).unwrap();
+
+        done.send(()).unwrap();
+        worker.join().unwrap();
+        // A thread that is gone is dropped, not an error
+        brakes.set_leashed(false).unwrap();
+        assert!(brakes.state().threads[&Work::Indexing].is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::policy::PowerSource;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PowerReading {
+    pub source: PowerSource,
+    pub charge_percent: Option<f32>, // None without a battery
+}
+
+/// THE BODY SENSORS
+/// Power source and temperature from sysfs. Both roots are parameters so a
+/// fixture tree can stand in for a laptop.
+pub struct Sensors {
+    power_root: PathBuf,
+    thermal_root: PathBuf,
+}
+
+impl Sensors {
+    pub fn new() -> Self {
+        Self::at(PathBuf::from("/sys/class/power_supply"), PathBuf::from("/sys/class/thermal"))
+    }
+
+    pub fn at(power_root: PathBuf, thermal_root: PathBuf) -> Self {
+        Self { power_root, thermal_root }
+    }
+
+    /// AC if any external supply (mains, USB-C PD) is online, battery if a
+    /// battery exists without one. None on machines with neither (VMs, some desktops).
+    pub fn read_power(&self) -> Option<PowerReading> {
+        let mut external_online = None;
+        let mut battery = None;
+
+        for supply in list_dir(&self.power_root) {
+            match read_trimmed(&supply.join("type")).as_deref() {
+                Some("Mains") | Some("USB") => {
+                    let online = read_trimmed(&supply.join("online")).is_some_and(|v| v == "1");
+                    external_online = Some(external_online.unwrap_or(false) || online);
+                }
+                Some("Battery") => {
+                    // Peripheral batteries (mice, headsets) are not in "System" scope
+                    if read_trimmed(&supply.join("scope")).is_some_and(|s| s == "Device") {
+                        continue;
+                    }
+                    let capacity = read_trimmed(&supply.join("capacity")).and_then(|v| v.parse::<f32>().ok());
+                    let discharging = read_trimmed(&supply.join("status")).is_some_and(|s| s == "Discharging");
+                    battery = Some((capacity, discharging));
+                }
+                _ => {}
+            }
+        }
+
+        let source = match (external_online, battery) {
+            (Some(true), _) => PowerSource::Ac,
+            (Some(false), Some(_)) => PowerSource::Battery,
+            // No supply entries, only the battery's own view
+            (None, Some((_, discharging))) => if discharging { PowerSource::Battery } else { PowerSource::Ac },
+            (Some(false), None) | (None, None) => return None,
+        };
+        Some(PowerReading { source, charge_percent: battery.and_then(|(capacity, _)| capacity) })
+    }
+
+    /// Hottest thermal zone in °C. Zones reporting 0 or absurd values
+    /// (unpopulated ACPI zones, disconnected sensors) are ignored.
+    pub fn read_temperature(&self) -> Option<f32> {
+        list_dir(&self.thermal_root).into_iter()
+            .filter(|zone| zone.file_name().is_some_and(|n| n.to_string_lossy().starts_with("thermal_zone")))
+            .filter_map(|zone| read_trimmed(&zone.join("temp"))?.parse::<f32>().ok())
+            .map(|millidegrees| millidegrees / 1000.0)
+            .filter(|c| *c > 0.0 && *c < 150.0)
+            .reduce(f32::max)
+    }
+}
+
+fn list_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(machine: &str) -> Sensors {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/sensors").join(machine);
+        Sensors::at(root.join("power_supply"), root.join("thermal"))
+    }
+
+    #[test]
+    fn laptop_on_battery() {
+        let sensors = fixture("laptop-battery");
+        let power = sensors.read_power().unwrap();
+        assert_eq!(power.source, PowerSource::Battery);
+        assert_eq!(power.charge_percent, Some(54.0));
+        // The bogus 0 °C ACPI zone is skipped, the hottest real one wins
+        assert_eq!(sensors.read_temperature(), Some(91.0));
+    }
+
+    #[test]
+    fn laptop_on_usb_c_charger() {
+        let power = fixture("laptop-usbc").read_power().unwrap();
+        assert_eq!(power.source, PowerSource::Ac);
+        assert_eq!(power.charge_percent, Some(80.0));
+    }
+
+    #[test]
+    fn desktop_without_sensors() {
+        let sensors = fixture("desktop");
+        assert_eq!(sensors.read_power(), None);
+        assert_eq!(sensors.read_temperature(), None);
+    }
+}
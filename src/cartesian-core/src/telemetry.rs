@@ -0,0 +1,272 @@
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use crate::inference::{Governor, GovernorState};
+use crate::policy::{GovernorInputs, GovernorPolicy};
+
+const STATES: [GovernorState; 4] = [
+    GovernorState::GodMode, GovernorState::Conscientious,
+    GovernorState::SidekickMode, GovernorState::PotatoMode,
+];
+
+/// One Governor tick: what it saw and what it decided.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceRecord {
+    pub t_ms: u64, // Unix epoch, milliseconds
+    pub inputs: GovernorInputs,
+    #[serde(default)]
+    pub unknown_apps: Vec<String>,
+    #[serde(default)]
+    pub signals: Vec<String>, // Predictor features: "launcher:steam", ...
+    #[serde(default)]
+    pub override_active: bool, // A manual hold; in older traces, the simulated game
+    pub state: GovernorState,
+    pub rule: String,
+}
+
+/// THE FLIGHT RECORDER
+/// Appends a TraceRecord per tick to `<dir>/trace.jsonl`. When the file passes
+/// `max_bytes` it becomes `trace.1.jsonl` (older files shift up) and only
+/// `keep` rotated files survive.
+pub struct TraceWriter {
+    dir: PathBuf,
+    file: BufWriter<File>,
+    bytes: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl TraceWriter {
+    pub fn open(dir: &Path, max_bytes: u64, keep: usize) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("{:?}: {}", dir, e))?;
+        let path = dir.join("trace.jsonl");
+        let file = OpenOptions::new().create(true).append(true).open(&path)
+            .map_err(|e| format!("{:?}: {}", path, e))?;
+        let bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { dir: dir.to_path_buf(), file: BufWriter::new(file), bytes, max_bytes, keep })
+    }
+
+    pub fn record(&mut self, record: &TraceRecord) -> Result<(), String> {
+        let mut line = serde_json::to_vec(record).map_err(|e| e.to_string())?;
+        line.push(b'\n');
+        self.file.write_all(&line).map_err(|e| e.to_string())?;
+        self.bytes += line.len() as u64;
+
+        if self.bytes >= self.max_bytes {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) {
+        let _ = self.file.flush();
+    }
+
+    fn rotate(&mut self) -> Result<(), String> {
+        self.file.flush().map_err(|e| e.to_string())?;
+        let _ = fs::remove_file(self.dir.join(format!("trace.{}.jsonl", self.keep)));
+        for i in (1..self.keep).rev() {
+            let _ = fs::rename(self.dir.join(format!("trace.{}.jsonl", i)), self.dir.join(format!("trace.{}.jsonl", i + 1)));
+        }
+        let current = self.dir.join("trace.jsonl");
+        if self.keep > 0 {
+            fs::rename(&current, self.dir.join("trace.1.jsonl")).map_err(|e| e.to_string())?;
+        }
+
+        let file = File::create(&current).map_err(|e| format!("{:?}: {}", current, e))?;
+        self.file = BufWriter::new(file);
+        self.bytes = 0;
+        Ok(())
+    }
+}
+
+/// Trace files in `dir`, oldest first.
+pub fn trace_files(dir: &Path) -> Vec<PathBuf> {
+    let mut rotated: Vec<(usize, PathBuf)> = fs::read_dir(dir).into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let n = name.strip_prefix("trace.")?.strip_suffix(".jsonl")?.parse().ok()?;
+            Some((n, e.path()))
+        })
+        .collect();
+    rotated.sort_by_key(|(n, _)| std::cmp::Reverse(*n)); // Highest number is oldest
+
+    let mut files: Vec<PathBuf> = rotated.into_iter().map(|(_, p)| p).collect();
+    let current = dir.join("trace.jsonl");
+    if current.exists() {
+        files.push(current);
+    }
+    files
+}
+
+/// Read records in file order. A torn last line (crash mid-write) is skipped.
+pub fn read_trace(files: &[PathBuf]) -> Result<Vec<TraceRecord>, String> {
+    let mut records = Vec::new();
+    for path in files {
+        let file = File::open(path).map_err(|e| format!("{:?}: {}", path, e))?;
+        for (n, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() { continue; }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => eprintln!("Telemetry: {:?}:{}: skipped ({})", path, n + 1, e),
+            }
+        }
+    }
+    Ok(records)
+}
+
+// --- THE SIMULATOR ---
+
+/// Where a sequence of states spent its time.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    pub transitions: usize,
+    pub time_in_state: [Duration; 4], // Indexed like STATES
+}
+
+impl Timeline {
+    pub fn time_in(&self, state: GovernorState) -> Duration {
+        self.time_in_state[STATES.iter().position(|s| *s == state).unwrap_or(0)]
+    }
+
+    fn add(&mut self, state: GovernorState, dt: Duration) {
+        if let Some(i) = STATES.iter().position(|s| *s == state) {
+            self.time_in_state[i] += dt;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ReplayReport {
+    pub ticks: usize,
+    pub sessions: usize,
+    pub recorded: Timeline,
+    pub candidate: Timeline,
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: Duration = self.recorded.time_in_state.iter().sum();
+        let pct = |d: Duration| if total.is_zero() { 0.0 } else { d.as_secs_f32() / total.as_secs_f32() * 100.0 };
+
+        writeln!(f, "ticks:        {} ({} sessions, {:.0?} covered)", self.ticks, self.sessions, total)?;
+        writeln!(f, "{:<16}{:>20}{:>20}", "", "recorded", "candidate")?;
+        writeln!(f, "{:<16}{:>20}{:>20}", "transitions", self.recorded.transitions, self.candidate.transitions)?;
+        for state in STATES {
+            let (r, c) = (self.recorded.time_in(state), self.candidate.time_in(state));
+            writeln!(
+                f, "{:<16}{:>20}{:>20}", state.label(),
+                format!("{:.0?} ({:.1}%)", r, pct(r)),
+                format!("{:.0?} ({:.1}%)", c, pct(c)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run recorded inputs through a fresh Governor with `policy`, on the trace's
+/// own clock. A gap longer than `max_gap` (app closed, machine asleep) starts a
+/// new session: the Governor restarts and the gap counts toward no state.
+pub fn replay(records: &[TraceRecord], policy: GovernorPolicy, max_gap: Duration) -> ReplayReport {
+    let mut report = ReplayReport { ticks: records.len(), sessions: 0, recorded: Timeline::default(), candidate: Timeline::default() };
+    let mut governor = Governor::new();
+    let epoch = Instant::now();
+    let mut prev: Option<(&TraceRecord, GovernorState)> = None;
+
+    for record in records {
+        let now = epoch + Duration::from_millis(record.t_ms.saturating_sub(records[0].t_ms));
+
+        let dt = prev.map(|(p, _)| Duration::from_millis(record.t_ms.saturating_sub(p.t_ms)));
+        if dt.is_none_or(|dt| dt > max_gap) {
+            governor = Governor::new();
+            governor.set_policy(policy.clone());
+            governor.set_logging(false);
+            report.sessions += 1;
+            prev = None;
+        }
+        let state = governor.decide_state_at(&record.inputs, now);
+
+        if let (Some((p, p_state)), Some(dt)) = (prev, dt) {
+            report.recorded.add(p.state, dt);
+            report.candidate.add(p_state, dt);
+            if record.state != p.state { report.recorded.transitions += 1; }
+            if state != p_state { report.candidate.transitions += 1; }
+        }
+        prev = Some((record, state));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(t_ms: u64, free_ram_gb: f32, state: GovernorState) -> TraceRecord {
+        TraceRecord {
+            t_ms,
+            inputs: GovernorInputs { free_ram_gb, ..GovernorInputs::idle() },
+            unknown_apps: Vec::new(),
+            signals: Vec::new(),
+            override_active: false,
+            state,
+            rule: String::new(),
+        }
+    }
+
+    /// RAM dipping under 2 GB every other second: the recording flapped,
+    /// a policy with hysteresis should drop once and stay down until it settles.
+    #[test]
+    fn replay_counts_transitions_and_time() {
+        let mut records = Vec::new();
+        for i in 0..20u64 {
+            let low = i % 2 == 1 && i < 10;
+            let state = if low { GovernorState::PotatoMode } else { GovernorState::GodMode };
+            records.push(record(i * 1000, if low { 1.0 } else { 8.0 }, state));
+        }
+
+        let report = replay(&records, GovernorPolicy::default(), Duration::from_secs(10));
+        assert_eq!(report.sessions, 1);
+        assert_eq!(report.recorded.transitions, 10);
+        // Emergency drop at 1 s, one upgrade once RAM has been fine for the 30 s dwell (never, in 19 s)
+        assert_eq!(report.candidate.transitions, 1);
+        assert_eq!(report.candidate.time_in(GovernorState::PotatoMode), Duration::from_secs(18));
+        assert_eq!(report.recorded.time_in(GovernorState::PotatoMode), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn gaps_split_sessions() {
+        let records = vec![
+            record(0, 8.0, GovernorState::GodMode),
+            record(1_000, 8.0, GovernorState::GodMode),
+            record(3_600_000, 8.0, GovernorState::GodMode),
+        ];
+        let report = replay(&records, GovernorPolicy::default(), Duration::from_secs(10));
+        assert_eq!(report.sessions, 2);
+        assert_eq!(report.recorded.time_in(GovernorState::GodMode), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn writer_rotates_and_reader_keeps_order() {
+        let dir = std::env::temp_dir().join(format!("cartesian-trace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut writer = TraceWriter::open(&dir, 600, 2).unwrap();
+        for i in 0..30 {
+            writer.record(&record(i, 8.0, GovernorState::GodMode)).unwrap();
+        }
+        writer.flush();
+
+        let files = trace_files(&dir);
+        assert_eq!(files.len(), 3, "{:?}", files);
+        let records = read_trace(&files).unwrap();
+        assert!(records.len() < 30, "oldest rotation should have been dropped");
+        assert!(records.windows(2).all(|w| w[0].t_ms < w[1].t_ms));
+        assert_eq!(records.last().unwrap().t_ms, 29);
+    }
+}
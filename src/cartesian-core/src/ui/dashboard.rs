@@ -1,8 +1,14 @@
-use iced::widget::{column, container, row, button, text};
+use std::time::Duration;
+use chrono::Timelike;
+use iced::widget::{column, container, row, button, text, Column};
 use iced::{Element, Length};
 
-use crate::{Cartesian, Message};
-use crate::lobotomy::AppCategory;
+use crate::{config, Cartesian, Message};
+use crate::inference::GovernorState;
+use crate::categories::AudioProfile;
+use crate::resources;
+use crate::lobotomy::{AppCategory, UnknownApp};
+use crate::policy::PowerSource;
 // Import the chat view
 use super::chat; 
 use super::style::{Palette, style_glass_card, style_background, label_header, label_main};
@@ -20,25 +26,123 @@ pub fn view<'a>(state: &'a Cartesian) -> Element<'a, Message> {
     let vision_color = if state.vision_status.contains("INPUT") { Palette::BLUE } else { Palette::ORANGE };
     let audio_state = state.mixer.get_state();
     let mic_color = if audio_state.mic_muted { Palette::RED } else { Palette::GREEN };
+    let music_text = match audio_state.profile {
+        AudioProfile::Normal => format!("MUSIC: {:.0}%", audio_state.music_vol * 100.0),
+        profile => format!("MUSIC: {:.0}% ({})", audio_state.effective_music_vol() * 100.0, profile.label()),
+    };
+    let categories = state.categories.all();
 
     // Helper strings
-    let cpu_ram_text = format!("CPU: {:.1}% | RAM: {:.1} GB FREE", state.cpu_usage, state.free_ram);
+    let vram_text = match state.vram {
+        Some(v) => format!("VRAM: {:.1}/{:.1} GB FREE", v.free_gb(), v.total_gb),
+        None => "VRAM: N/A".to_string(),
+    };
+    let cpu_ram_text = format!("CPU: {:.1}% | RAM: {:.1} GB FREE | {}", state.cpu_usage, state.free_ram, vram_text);
+    let stall_text = match state.pressure {
+        Some(p) => format!("STALL: CPU {:.1}% | MEM {:.1}% | IO {:.1}%", p.cpu.some.avg10, p.memory.some.avg10, p.io.some.avg10),
+        None => "STALL: N/A".to_string(),
+    };
+    let reason_text = match state.governor.pending_target() {
+        Some(target) => format!("RULE: {} -> {} (SETTLING)", state.governor.reason(), target.label()),
+        None => format!("RULE: {}", state.governor.reason()),
+    };
+    let power_text = match state.power {
+        Some(p) => match (p.source, p.charge_percent) {
+            (PowerSource::Battery, Some(charge)) => format!("POWER: BATTERY {:.0}%", charge),
+            (PowerSource::Battery, None) => "POWER: BATTERY".to_string(),
+            (PowerSource::Ac, _) => "POWER: AC".to_string(),
+        },
+        None => "POWER: N/A".to_string(),
+    };
+    let body_text = match state.temperature_c {
+        Some(t) => format!("{} | TEMP: {:.0}°C", power_text, t),
+        None => format!("{} | TEMP: N/A", power_text),
+    };
+    let ceiling_text = match &state.ceiling {
+        Some((app, max)) => format!("CEILING: {} ({})", max.label(), app),
+        None => "CEILING: NONE".to_string(),
+    };
+    let override_text = match (state.governor.hold_remaining(), state.governor.active_schedule()) {
+        (Some((held, left)), _) => format!("HOLD: {} ({} LEFT)", held.label(), countdown(left)),
+        (None, Some(schedule)) => format!(
+            "SCHEDULE: {} -> {} ({} LEFT)", schedule.name, schedule.state.label(), countdown(until_hour(schedule.hours[1]))
+        ),
+        (None, None) => "OVERRIDE: NONE (POLICY)".to_string(),
+    };
+    let hold_buttons = row(
+        [
+            (GovernorState::GodMode, "GOD"),
+            (GovernorState::Conscientious, "CONSC"),
+            (GovernorState::SidekickMode, "SIDEKICK"),
+            (GovernorState::PotatoMode, "POTATO"),
+        ].into_iter().map(|(s, label)| button(text(label).size(12)).on_press(Message::Hold(s)).padding(6).into())
+    )
+    .push(button(text("RELEASE").size(12))
+        .on_press_maybe(state.governor.hold_remaining().map(|_| Message::ReleaseHold))
+        .padding(6))
+    .spacing(5);
+    let focus_text = match &state.focus {
+        Some(f) => format!(
+            "FOCUS: {} — {}{} | WS {}",
+            f.class, f.title, if f.fullscreen { " [FULLSCREEN]" } else { "" }, f.workspace.as_deref().unwrap_or("?")
+        ),
+        None => "FOCUS: UNKNOWN".to_string(),
+    };
+    let gb = |v: Option<f32>| v.map_or("N/A".to_string(), |v| format!("{:.1} GB", v));
+    let own = &state.own_usage;
+    let own_text = format!(
+        "CPU: {:.0}% | RSS: {} | VRAM: {} | {} THREADS",
+        own.cpu_percent, gb(own.rss_gb()), gb(own.gpu_gb()), own.threads.map_or("?".to_string(), |n| n.to_string())
+    );
+    let subsystem_text = own.subsystems.iter()
+        .map(|(subsystem, usage)| {
+            let held: String = [(usage.resident.ram_gb, "RAM"), (usage.resident.vram_gb, "VRAM")].iter()
+                .filter(|(gb, _)| *gb > 0.0)
+                .map(|(gb, kind)| format!(" {:.1} GB {}", gb, kind))
+                .collect();
+            format!("{} {:.0}% ({}){}", subsystem.label(), usage.cpu_percent, usage.busy_threads, held)
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let brakes = resources::controller();
+    let paused: Vec<String> = resources::WORK.iter()
+        .filter_map(|work| {
+            let by = brakes.paused_by(*work);
+            (!by.is_empty()).then(|| format!(
+                "{} ({})",
+                work.name().to_uppercase(),
+                by.iter().map(|r| r.label()).collect::<Vec<_>>().join("+"),
+            ))
+        })
+        .collect();
+    let paused_text = format!(
+        "PAUSED: {}{}",
+        if paused.is_empty() { "NONE".to_string() } else { paused.join(" | ") },
+        if brakes.is_leashed() { " | LEASHED" } else { "" },
+    );
+    let memory_text = format!("MEMORY: {} ENGRAMS | {} QUEUED", state.memory.engram_count(), state.memory.pending_jobs());
     
     // --- LEFT COLUMN: TELEMETRY ---
     let telemetry_col = column![
         label_header("CARTESIAN OS // CORE".to_string()).size(24),
         label_header(cpu_ram_text),
+        label_header(stall_text),
+        label_header(memory_text),
 
         // A. GOVERNOR
         container(column![
             label_header("AI GOVERNOR".to_string()),
             label_main(state.brain_state.clone(), brain_color), 
+            label_header(reason_text),
+            label_header(body_text),
+            label_header(ceiling_text),
         ].spacing(5)).padding(20).style(style_glass_card(brain_color)),
 
         // B. CONTEXT
         container(column![
             label_header("SYSTEM CONTEXT".to_string()),
             label_main(state.status.clone(), context_color),
+            label_header(focus_text),
         ].spacing(5)).padding(20).style(style_glass_card(context_color)),
 
         // C. WITNESS
@@ -52,13 +156,33 @@ pub fn view<'a>(state: &'a Cartesian) -> Element<'a, Message> {
             label_header("AUDIO MIXER".to_string()),
             row![
                 text(format!("GAME: {:.0}%", audio_state.game_vol * 100.0)).size(16).color(Palette::TEXT_MAIN),
+                text(music_text).size(16).color(Palette::TEXT_MAIN),
                 text(format!("MIC: {}", if audio_state.mic_muted {"MUT"} else {"ON"})).size(16).color(mic_color),
             ].spacing(20)
         ].spacing(10)).padding(20).style(style_glass_card(Palette::TEXT_DIM)),
 
-        button(if state.debug_override { "STOP SIM" } else { "SIMULATE GAME" })
-            .on_press(Message::ToggleOverride)
-            .padding(10)
+        // E. UNKNOWN APPS (answers go to the process registry)
+        container(column![
+            label_header(format!("UNKNOWN APPS ({})", state.unknown_apps.len())),
+            Column::with_children(
+                state.unknown_apps.iter().take(config::UNKNOWN_APPS_SHOWN).map(|app| unknown_app_row(app, &categories))
+            ).spacing(10),
+        ].spacing(10)).padding(20).style(style_glass_card(Palette::ORANGE)),
+
+        // F. FOOTPRINT (what the AI stack itself costs; CPU per subsystem, busy threads in brackets; held work)
+        container(column![
+            label_header("CARTESIAN FOOTPRINT".to_string()),
+            label_header(own_text),
+            label_header(subsystem_text),
+            label_header(paused_text),
+        ].spacing(5)).padding(20).style(style_glass_card(Palette::TEXT_DIM)),
+
+        // G. OVERRIDES (each press holds the mode for another hour)
+        container(column![
+            label_header("MANUAL HOLD (+1H PER PRESS)".to_string()),
+            label_header(override_text),
+            hold_buttons,
+        ].spacing(10)).padding(20).style(style_glass_card(Palette::TEXT_DIM)),
     ]
     .spacing(20)
     .width(Length::FillPortion(1)); // Take 1/3 width
@@ -83,4 +207,43 @@ pub fn view<'a>(state: &'a Cartesian) -> Element<'a, Message> {
         .height(Length::Fill)
         .style(style_background)
         .into()
-}
\ No newline at end of file
+}
+
+/// H:MM:SS
+fn countdown(left: Duration) -> String {
+    let secs = left.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Time until the next local `hour`:00.
+fn until_hour(hour: u8) -> Duration {
+    let now = chrono::Local::now();
+    let elapsed = now.num_seconds_from_midnight() as u64;
+    let target = hour as u64 * 3600;
+    Duration::from_secs((target + 86_400 - elapsed) % 86_400)
+}
+
+/// One unknown app: usage, the suggested category (highlighted) and a button per category.
+fn unknown_app_row<'a>(app: &'a UnknownApp, categories: &[AppCategory]) -> Element<'a, Message> {
+    let usage = format!("{}  {:.0} MB | CPU {:.0}%", app.name, app.memory_bytes as f32 / 1_048_576.0, app.cpu_percent);
+    let hint = match (app.suggestion, &app.exe) {
+        (Some(category), _) => format!("SUGGESTED: {}", category.name().to_uppercase()),
+        (None, Some(exe)) => exe.clone(),
+        (None, None) => String::new(),
+    };
+    let buttons = row(categories.iter().map(|&category| {
+        let label = category.name().to_uppercase();
+        button(text(label).size(11))
+            .style(if app.suggestion == Some(category) { button::primary } else { button::secondary })
+            .on_press(Message::Learn(app.name.clone(), category))
+            .padding(4)
+            .into()
+    }))
+    .spacing(4);
+
+    column![
+        text(usage).size(14).color(Palette::TEXT_MAIN),
+        label_header(hint),
+        buttons,
+    ].spacing(4).into()
+}
@@ -0,0 +1,202 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use argon2::Argon2;
+use chacha20poly1305::{XChaCha20Poly1305, XNonce, Key};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use crate::config;
+
+// --- SEALED LAYOUT ---
+// [MAGIC 4][VERSION 1][KDF 1][SALT 16][NONCE 24][CIPHERTEXT + TAG]
+const MAGIC: &[u8; 4] = b"CVLT";
+const VERSION: u8 = 1;
+const KDF_KEYRING: u8 = 0;
+const KDF_ARGON2: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = 4 + 1 + 1 + SALT_LEN + NONCE_LEN;
+
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    /// 256-bit random key stored in the login keyring. Created on first use.
+    Keyring,
+    /// User passphrase, stretched with Argon2id and a per-file salt.
+    Passphrase(String),
+}
+
+/// THE VAULT
+/// Authenticated encryption (XChaCha20-Poly1305) for the on-disk Hippocampus.
+///
+/// The key comes either from the user keyring (Secret Service, via `secret-tool`)
+/// or from a passphrase stretched with Argon2id. The salt is only meaningful for
+/// passphrase keys but is always present so the header has a fixed size.
+pub struct Vault {
+    source: KeySource,
+}
+
+impl Vault {
+    pub fn new(source: KeySource) -> Self {
+        Self { source }
+    }
+
+    /// Reads the key source from the environment.
+    /// CARTESIAN_VAULT=keyring | passphrase (with CARTESIAN_VAULT_PASSPHRASE).
+    /// Returns None when encryption at rest is not enabled.
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("CARTESIAN_VAULT").ok()?.as_str() {
+            "keyring" => Some(Self::new(KeySource::Keyring)),
+            "passphrase" => std::env::var("CARTESIAN_VAULT_PASSPHRASE").ok()
+                .map(|p| Self::new(KeySource::Passphrase(p))),
+            _ => None,
+        }
+    }
+
+    /// True if the bytes carry a Vault header.
+    pub fn is_sealed(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let (kdf, key) = match &self.source {
+            KeySource::Keyring => (KDF_KEYRING, keyring_key(true)?),
+            KeySource::Passphrase(p) => (KDF_ARGON2, derive_key(p, &salt)?),
+        };
+
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.push(kdf);
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+
+        // The header is bound as associated data so it can't be swapped.
+        let ciphertext = cipher
+            .encrypt(&nonce, chacha20poly1305::aead::Payload { msg: plaintext, aad: &header })
+            .map_err(|_| "Vault: Encryption failed.".to_string())?;
+
+        header.extend_from_slice(&ciphertext);
+        Ok(header)
+    }
+
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        if sealed.len() < HEADER_LEN || !Self::is_sealed(sealed) {
+            return Err("Vault: Not a sealed store.".to_string());
+        }
+        if sealed[4] != VERSION {
+            return Err(format!("Vault: Unsupported version {}.", sealed[4]));
+        }
+
+        let salt = &sealed[6..6 + SALT_LEN];
+        let key = match (sealed[5], &self.source) {
+            (KDF_KEYRING, KeySource::Keyring) => keyring_key(false)?,
+            (KDF_ARGON2, KeySource::Passphrase(p)) => derive_key(p, salt)?,
+            _ => return Err("Vault: Store was sealed with a different key source.".to_string()),
+        };
+
+        let (header, ciphertext) = sealed.split_at(HEADER_LEN);
+        let nonce = XNonce::from_slice(&header[6 + SALT_LEN..]);
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+
+        cipher
+            .decrypt(nonce, chacha20poly1305::aead::Payload { msg: ciphertext, aad: header })
+            .map_err(|_| "Vault: Wrong key or corrupted store.".to_string())
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Vault: Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Fetch the key from the Secret Service. With `create`, a missing key is generated and stored.
+fn keyring_key(create: bool) -> Result<[u8; 32], String> {
+    let lookup = Command::new("secret-tool")
+        .args(["lookup", "service", config::KEYRING_SERVICE])
+        .output()
+        .map_err(|e| format!("Vault: secret-tool unavailable: {}", e))?;
+
+    let stored = String::from_utf8_lossy(&lookup.stdout).trim().to_string();
+    if lookup.status.success() && !stored.is_empty() {
+        return decode_hex(&stored).ok_or_else(|| "Vault: Keyring entry is malformed.".to_string());
+    }
+    if !create {
+        return Err("Vault: No key found in keyring.".to_string());
+    }
+
+    let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+    let encoded: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+
+    let mut child = Command::new("secret-tool")
+        .args(["store", "--label=CartesianOS Hippocampus", "service", config::KEYRING_SERVICE])
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Vault: secret-tool unavailable: {}", e))?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(encoded.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err("Vault: Failed to store key in keyring.".to_string());
+    }
+
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&key);
+    Ok(out)
+}
+
+fn decode_hex(s: &str) -> Option<[u8; 32]> {
+    if s.len() != 64 || !s.is_ascii() { return None; }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(passphrase: &str) -> Vault {
+        Vault::new(KeySource::Passphrase(passphrase.to_string()))
+    }
+
+    #[test]
+    fn sealed_data_opens_with_the_same_passphrase() {
+        let plaintext = b"engrams and synapses";
+        let sealed = vault("correct horse").seal(plaintext).unwrap();
+
+        assert!(Vault::is_sealed(&sealed));
+        assert!(!Vault::is_sealed(plaintext));
+        assert!(!sealed.windows(plaintext.len()).any(|w| w == plaintext));
+        assert_eq!(vault("correct horse").open(&sealed).unwrap(), plaintext);
+        // A fresh salt and nonce every time
+        assert_ne!(vault("correct horse").seal(plaintext).unwrap(), sealed);
+    }
+
+    #[test]
+    fn wrong_keys_and_tampering_are_refused() {
+        let sealed = vault("correct horse").seal(b"engrams").unwrap();
+
+        assert!(vault("battery staple").open(&sealed).is_err());
+        assert!(Vault::new(KeySource::Keyring).open(&sealed).is_err());
+        assert!(vault("correct horse").open(b"engrams").is_err());
+
+        let mut flipped = sealed.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(vault("correct horse").open(&flipped).is_err());
+        // The header is authenticated too
+        let mut salted = sealed.clone();
+        salted[HEADER_LEN - 1] ^= 1;
+        assert!(vault("correct horse").open(&salted).is_err());
+    }
+}
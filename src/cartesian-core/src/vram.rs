@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use nvml_wrapper::Nvml;
+use nvml_wrapper::enums::device::UsedGpuMemory;
+use serde::{Serialize, Deserialize};
+use crate::config;
+use crate::inference::{EngineProfile, Placement};
+
+const GIB: f32 = 1_073_741_824.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VramReading {
+    pub total_gb: f32,
+    pub used_gb: f32,
+}
+
+impl VramReading {
+    pub fn free_gb(&self) -> f32 {
+        (self.total_gb - self.used_gb).max(0.0)
+    }
+}
+
+/// THE VRAM PROBE
+/// One source of GPU memory numbers. `read` returns None when the device
+/// stops answering (driver reset, eGPU unplugged); the Governor then treats
+/// VRAM as unknown instead of guessing.
+pub trait VramProbe: Send {
+    fn name(&self) -> &str;
+    fn read(&mut self) -> Option<VramReading>;
+
+    /// GPU memory per pid, in bytes, where the driver reports it.
+    fn process_usage(&mut self) -> HashMap<u32, u64> {
+        HashMap::new()
+    }
+}
+
+/// Pick the first probe that works on this machine:
+/// 1. `CARTESIAN_VRAM_MOCK=<file>` (testing without a GPU)
+/// 2. NVML (NVIDIA, loaded at runtime so the binary still starts without the driver)
+/// 3. amdgpu sysfs
+pub fn detect() -> Option<Box<dyn VramProbe>> {
+    if let Ok(path) = std::env::var("CARTESIAN_VRAM_MOCK") {
+        return Some(Box::new(FileProbe::new(PathBuf::from(path))));
+    }
+    if let Some(probe) = NvmlProbe::new(0) {
+        return Some(Box::new(probe));
+    }
+    if let Some(probe) = AmdSysfsProbe::detect(Path::new("/sys/class/drm")) {
+        return Some(Box::new(probe));
+    }
+    None
+}
+
+/// Memory a model needs once resident: weights plus KV cache and activations.
+pub fn model_footprint_gb(model_name: &str) -> Option<f32> {
+    let path = Path::new(&config::get_model_dir()).join(model_name);
+    let size = fs::metadata(path).ok()?.len();
+    Some(size as f32 / GIB * config::VRAM_MODEL_OVERHEAD)
+}
+
+/// VRAM a profile's model takes once loaded: nothing on the CPU or unloaded.
+pub fn profile_footprint_gb(profile: &EngineProfile) -> Option<f32> {
+    match (&profile.model, profile.placement) {
+        (Some(model), Placement::Gpu) => model_footprint_gb(model),
+        _ => Some(0.0),
+    }
+}
+
+/// Free VRAM left over once `model` is loaded. Memory held by our own
+/// resident model counts as available, since swapping models releases it.
+pub fn headroom_gb(reading: &VramReading, resident_gb: f32, model_gb: f32) -> f32 {
+    reading.free_gb() + resident_gb - model_gb
+}
+
+// --- NVIDIA ---
+
+pub struct NvmlProbe {
+    nvml: Nvml,
+    index: u32,
+}
+
+impl NvmlProbe {
+    pub fn new(index: u32) -> Option<Self> {
+        let nvml = Nvml::init().ok()?;
+        // Fail detection if the device itself is missing, not just on first read
+        nvml.device_by_index(index).ok()?;
+        Some(Self { nvml, index })
+    }
+}
+
+impl VramProbe for NvmlProbe {
+    fn name(&self) -> &str { "NVML" }
+
+    fn read(&mut self) -> Option<VramReading> {
+        let info = self.nvml.device_by_index(self.index).ok()?.memory_info().ok()?;
+        Some(VramReading {
+            total_gb: info.total as f32 / GIB,
+            used_gb: info.used as f32 / GIB,
+        })
+    }
+
+    fn process_usage(&mut self) -> HashMap<u32, u64> {
+        let mut usage = HashMap::new();
+        let Ok(device) = self.nvml.device_by_index(self.index) else { return usage; };
+        let graphics = device.running_graphics_processes().unwrap_or_default();
+        let compute = device.running_compute_processes().unwrap_or_default();
+        for process in graphics.into_iter().chain(compute) {
+            if let UsedGpuMemory::Used(bytes) = process.used_gpu_memory {
+                *usage.entry(process.pid).or_insert(0) += bytes;
+            }
+        }
+        usage
+    }
+}
+
+// --- AMD ---
+
+/// Reads `mem_info_vram_{used,total}` from an amdgpu device directory.
+pub struct AmdSysfsProbe {
+    device: PathBuf,
+}
+
+impl AmdSysfsProbe {
+    pub fn new(device: PathBuf) -> Self {
+        Self { device }
+    }
+
+    /// First `<drm_root>/card*/device` exposing VRAM counters. The root is a
+    /// parameter so tests can point it at a fake tree.
+    pub fn detect(drm_root: &Path) -> Option<Self> {
+        let mut cards: Vec<PathBuf> = fs::read_dir(drm_root).ok()?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.file_name().is_some_and(|n| {
+                let n = n.to_string_lossy();
+                n.starts_with("card") && !n.contains('-') // Skip connectors (card0-DP-1)
+            }))
+            .collect();
+        cards.sort();
+
+        cards.into_iter()
+            .map(|card| card.join("device"))
+            .find(|device| device.join("mem_info_vram_used").exists())
+            .map(Self::new)
+    }
+
+    fn read_bytes(&self, file: &str) -> Option<u64> {
+        fs::read_to_string(self.device.join(file)).ok()?.trim().parse().ok()
+    }
+}
+
+impl VramProbe for AmdSysfsProbe {
+    fn name(&self) -> &str { "amdgpu" }
+
+    fn read(&mut self) -> Option<VramReading> {
+        Some(VramReading {
+            total_gb: self.read_bytes("mem_info_vram_total")? as f32 / GIB,
+            used_gb: self.read_bytes("mem_info_vram_used")? as f32 / GIB,
+        })
+    }
+}
+
+// --- MOCK ---
+
+/// A VramReading as JSON on disk, re-read every call so it can be edited live.
+pub struct FileProbe {
+    path: PathBuf,
+}
+
+impl FileProbe {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl VramProbe for FileProbe {
+    fn name(&self) -> &str { "mock" }
+
+    fn read(&mut self) -> Option<VramReading> {
+        let data = fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::GovernorState;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cartesian-vram-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn file_probe_follows_edits() {
+        let dir = scratch("mock");
+        let file = dir.join("vram.json");
+        let mut probe = FileProbe::new(file.clone());
+        assert_eq!(probe.read(), None);
+
+        fs::write(&file, r#"{ "total_gb": 12.0, "used_gb": 9.5 }"#).unwrap();
+        assert_eq!(probe.read().unwrap().free_gb(), 2.5);
+
+        fs::write(&file, r#"{ "total_gb": 12.0, "used_gb": 2.0 }"#).unwrap();
+        assert_eq!(probe.read().unwrap().free_gb(), 10.0);
+    }
+
+    #[test]
+    fn amd_probe_finds_card_with_vram_counters() {
+        let root = scratch("drm");
+        // card0 is an iGPU without VRAM counters, card1-DP-1 a connector
+        fs::create_dir_all(root.join("card0/device")).unwrap();
+        fs::create_dir_all(root.join("card1-DP-1")).unwrap();
+        let device = root.join("card1/device");
+        fs::create_dir_all(&device).unwrap();
+        fs::write(device.join("mem_info_vram_total"), "8589934592\n").unwrap();
+        fs::write(device.join("mem_info_vram_used"), "2147483648\n").unwrap();
+
+        let mut probe = AmdSysfsProbe::detect(&root).unwrap();
+        let reading = probe.read().unwrap();
+        assert_eq!(reading.total_gb, 8.0);
+        assert_eq!(reading.free_gb(), 6.0);
+    }
+
+    #[test]
+    fn only_gpu_profiles_need_vram() {
+        let god = EngineProfile::builtin(GovernorState::GodMode);
+        let on_cpu = EngineProfile { placement: Placement::Cpu, ..god.clone() };
+        let unloaded = EngineProfile { model: None, ..god.clone() };
+        assert_eq!(profile_footprint_gb(&on_cpu), Some(0.0));
+        assert_eq!(profile_footprint_gb(&unloaded), Some(0.0));
+        // A model that isn't on disk has no known footprint
+        let missing = EngineProfile { model: Some("not-downloaded.gguf".to_string()), ..god };
+        assert_eq!(profile_footprint_gb(&missing), None);
+    }
+
+    #[test]
+    fn resident_model_counts_as_headroom() {
+        let reading = VramReading { total_gb: 8.0, used_gb: 6.0 };
+        // 2 GB free is not enough for a 3 GB model...
+        assert!(headroom_gb(&reading, 0.0, 3.0) < 0.0);
+        // ...unless 3 GB of the used memory is our own model
+        assert!(headroom_gb(&reading, 3.0, 3.0) >= 0.0);
+    }
+}
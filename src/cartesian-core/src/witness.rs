@@ -1,8 +1,12 @@
 use std::fs::OpenOptions;
+use std::io::{Cursor, Write};
 use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Instant;
 use memmap2::MmapMut;
 use crate::config;
-use image::{DynamicImage, ImageBuffer, Rgba};
+use crate::inference::BackgroundBudget;
+use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
 
 const OFF_STATUS: usize = 0;
 const OFF_WIDTH: usize = 4;
@@ -32,6 +36,111 @@ impl VisualCortex {
         ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(self.width, self.height, self.data.clone())
             .map(DynamicImage::ImageRgba8)
     }
+
+    /// Downscaled PNG for storing alongside a visual engram.
+    pub fn thumbnail_png(&self, max_size: u32) -> Option<Vec<u8>> {
+        let thumb = self.to_dynamic_image()?.thumbnail(max_size, max_size);
+        encode_png(&thumb)
+    }
+
+    /// 16x16 grid of mean luma (0-1). Cheap enough to compute every tick.
+    /// Channel order doesn't matter since R, G and B are weighted equally.
+    pub fn signature(&self) -> Vec<f32> {
+        const GRID: u32 = 16;
+        let mut sig = Vec::with_capacity((GRID * GRID) as usize);
+        if self.width == 0 || self.height == 0 { return sig; }
+
+        for gy in 0..GRID {
+            for gx in 0..GRID {
+                let x = (gx * self.width / GRID + self.width / (GRID * 2)).min(self.width - 1);
+                let y = (gy * self.height / GRID + self.height / (GRID * 2)).min(self.height - 1);
+                let i = ((y * self.width + x) * 4) as usize;
+                let luma = match self.data.get(i..i + 3) {
+                    Some(px) => (px[0] as f32 + px[1] as f32 + px[2] as f32) / (3.0 * 255.0),
+                    None => 0.0,
+                };
+                sig.push(luma);
+            }
+        }
+        sig
+    }
+}
+
+/// A sampled frame, described and ready to become a visual engram.
+/// The description is the OCR text only: the Engine doesn't load the Moondream
+/// projector, so frames aren't captioned and a screen without text is skipped.
+#[derive(Debug, Clone)]
+pub struct VisualObservation {
+    pub captured_at: u64, // Unix seconds
+    pub description: String,
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// Decides which frames become visual engrams: only on visible change,
+/// never more often than the budget's interval, and never while paused.
+pub struct VisualSampler {
+    last_sample: Option<Instant>,
+    last_signature: Vec<f32>,
+}
+
+impl VisualSampler {
+    pub fn new() -> Self {
+        Self {
+            last_sample: None,
+            last_signature: Vec::new(),
+        }
+    }
+
+    /// The budget comes from the Governor's profile: paused while the GPU must
+    /// be left alone (gaming, low memory), sampled less often when throttled.
+    pub fn should_sample(&mut self, frame: &VisualCortex, budget: BackgroundBudget) -> bool {
+        self.should_sample_at(frame, budget, Instant::now())
+    }
+
+    fn should_sample_at(&mut self, frame: &VisualCortex, budget: BackgroundBudget, now: Instant) -> bool {
+        let interval = match budget {
+            BackgroundBudget::Full => config::VISUAL_SAMPLE_INTERVAL,
+            BackgroundBudget::Throttled => config::VISUAL_THROTTLED_INTERVAL,
+            BackgroundBudget::Paused => return false,
+        };
+        if let Some(last) = self.last_sample {
+            if now.duration_since(last) < interval { return false; }
+        }
+
+        let signature = frame.signature();
+        let changed = self.last_signature.len() != signature.len() || {
+            let delta: f32 = signature.iter().zip(&self.last_signature).map(|(a, b)| (a - b).abs()).sum();
+            delta / signature.len().max(1) as f32 > config::VISUAL_CHANGE_THRESHOLD
+        };
+        if !changed { return false; }
+
+        self.last_sample = Some(now);
+        self.last_signature = signature;
+        true
+    }
+}
+
+/// OCR via the `tesseract` CLI. Returns None if it isn't installed or finds no text.
+pub fn read_text(image: &DynamicImage) -> Option<String> {
+    let png = encode_png(image)?;
+    let mut child = Command::new("tesseract")
+        .args(["stdin", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(&png).ok()?;
+
+    let output = child.wait_with_output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).split_whitespace().collect::<Vec<_>>().join(" ");
+    if output.status.success() && !text.is_empty() { Some(text) } else { None }
+}
+
+fn encode_png(image: &DynamicImage) -> Option<Vec<u8>> {
+    let mut buf = Vec::new();
+    image.write_to(&mut Cursor::new(&mut buf), ImageFormat::Png).ok()?;
+    Some(buf)
 }
 
 impl Eye {
@@ -97,4 +206,42 @@ impl Eye {
 
         Some(VisualCortex { width, height, data })
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn frame(luma: u8) -> VisualCortex {
+        VisualCortex { width: 32, height: 32, data: [luma, luma, luma, 255].repeat(32 * 32) }
+    }
+
+    #[test]
+    fn frames_are_sampled_on_change_and_rate_limited() {
+        let mut sampler = VisualSampler::new();
+        let t0 = Instant::now();
+        let later = |interval: Duration| t0 + interval + Duration::from_secs(1);
+
+        assert!(sampler.should_sample_at(&frame(0), BackgroundBudget::Full, t0));
+        // The same screen again is not worth a second engram
+        assert!(!sampler.should_sample_at(&frame(0), BackgroundBudget::Full, later(config::VISUAL_SAMPLE_INTERVAL)));
+        // A visible change waits out the interval
+        assert!(!sampler.should_sample_at(&frame(200), BackgroundBudget::Full, t0 + Duration::from_secs(1)));
+        assert!(sampler.should_sample_at(&frame(200), BackgroundBudget::Full, later(config::VISUAL_SAMPLE_INTERVAL)));
+    }
+
+    #[test]
+    fn throttled_waits_longer_and_paused_never_samples() {
+        let t0 = Instant::now();
+        let mut throttled = VisualSampler::new();
+        assert!(throttled.should_sample_at(&frame(0), BackgroundBudget::Throttled, t0));
+        assert!(!throttled.should_sample_at(&frame(200), BackgroundBudget::Throttled, t0 + config::VISUAL_SAMPLE_INTERVAL * 2));
+        assert!(throttled.should_sample_at(&frame(200), BackgroundBudget::Throttled, t0 + config::VISUAL_THROTTLED_INTERVAL));
+
+        let mut paused = VisualSampler::new();
+        assert!(!paused.should_sample_at(&frame(0), BackgroundBudget::Paused, t0));
+        assert!(!paused.should_sample_at(&frame(200), BackgroundBudget::Paused, t0 + config::VISUAL_THROTTLED_INTERVAL));
+        // Pausing doesn't count as a sample
+        assert!(paused.should_sample_at(&frame(0), BackgroundBudget::Full, t0));
+    }
+}